@@ -1,11 +1,32 @@
+mod rpc;
 mod scanners;
+mod sinks;
+mod storage;
+mod tokens;
 mod utils;
 
-use crate::utils::{config::AppConfig, log::Logger};
-use scanners::{bsc::BscScanner, tron::TronScanner, BlockchainScanner};
-use std::error::Error;
+use crate::scanners::registry::ScannerRegistry;
+use crate::scanners::types::{ScannedBlock, ScannedTransaction};
+use crate::scanners::watchlist::Watchlist;
+use crate::sinks::TransactionSink;
+use crate::storage::checkpoint::CheckpointStore;
+use crate::storage::failed_blocks::FailedBlockStore;
+use crate::storage::token_metadata::TokenMetadataStore;
+use crate::storage::watchlist::WatchlistStore;
+use crate::tokens::TokenRegistry;
+use crate::utils::config::{AppConfig, SchedulerConfig};
+use crate::utils::error::AppError;
+use crate::utils::log::Logger;
+use futures::stream::{self, StreamExt};
+use scanners::BlockchainScanner;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
+
+/// 后台任务每轮重新扫描的死信区块数量
+const FAILED_BLOCK_BATCH_SIZE: i64 = 20;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -18,35 +39,85 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Logger initialized");
     info!("Starting blockchain scanner...");
 
-    let mut scanners: Vec<Box<dyn BlockchainScanner>> = Vec::new();
+    let pool = storage::connect(&config.database).await?;
+    let checkpoints = CheckpointStore::new(pool.clone()).await?;
+    let failed_blocks = FailedBlockStore::new(pool.clone()).await?;
+    let watchlist_store = WatchlistStore::new(pool.clone()).await?;
+    let token_store = TokenMetadataStore::new(pool.clone()).await?;
+    let sinks: Arc<Vec<Box<dyn TransactionSink>>> =
+        Arc::new(sinks::build(&config.sinks, &pool).await?);
 
-    if config.tron.enable {
-        let tron_scanner = TronScanner::new(&config.tron.api_url, config.tron.start_block)?;
-        scanners.push(Box::new(tron_scanner));
-    }
+    let mut scanners: Vec<(Arc<dyn BlockchainScanner>, ScanSettings)> = Vec::new();
+
+    let registry = ScannerRegistry::default();
+    let mut chain_names = HashSet::new();
 
-    if config.bsc.enable {
-        let bsc_scanner = BscScanner::new(&config.bsc.api_url, config.bsc.start_block)?;
-        scanners.push(Box::new(bsc_scanner));
+    for chain in config.chains.iter().filter(|chain| chain.enable) {
+        // 链名称是断点和监控地址的键，重复会导致多个扫描器互相覆盖断点
+        if !chain_names.insert(chain.name.as_str()) {
+            return Err(format!("Duplicate chain name: {}", chain.name).into());
+        }
+
+        let kind = registry.get(&chain.kind)?;
+        let watchlist = Arc::new(Watchlist::new(
+            &chain.name,
+            kind.address_format,
+            &chain.watchlist,
+        ));
+        start_watchlist(watchlist.clone(), watchlist_store.clone()).await?;
+        let tokens = Arc::new(TokenRegistry::new(&chain.name, token_store.clone()));
+        let scanner: Arc<dyn BlockchainScanner> =
+            Arc::from((kind.constructor)(chain, watchlist, tokens)?);
+        match scanner.verify_node().await {
+            Ok(()) => {}
+            Err(e @ AppError::ChainMismatch(_)) => return Err(e.into()),
+            // 节点暂时不可达时不阻止启动，扫块循环会继续重试
+            Err(e) => warn!("Could not verify {} node: {:?}", chain.name, e),
+        }
+        let settings = ScanSettings::new(
+            &config.scheduler,
+            chain.interval_seconds,
+            chain.confirmations,
+        );
+        info!("Configured {} scanner {}", chain.kind, chain.name);
+        scanners.push((scanner, settings));
     }
 
     let (tx, mut rx) = mpsc::channel(100);
 
-    for scanner in scanners {
+    for (scanner, settings) in scanners {
         let tx = tx.clone();
+        let checkpoints = checkpoints.clone();
+        let failed_blocks = failed_blocks.clone();
+        let sinks = sinks.clone();
         let chain_name = scanner.chain_name().to_string();
-        tokio::spawn(async move {
-            let mut current_block = scanner.get_start_block();
-            loop {
-                if let Err(e) = scan_block(&*scanner, current_block, &chain_name).await {
-                    error!("Error scanning {} block: {:?}", chain_name, e);
-                }
-                if tx.send(()).await.is_err() {
-                    break;
-                }
-                current_block += 1;
-                tokio::time::sleep(tokio::time::Duration::from_secs(100)).await;
+
+        // 有断点记录时从断点的下一个区块继续，否则从配置的起始区块开始
+        let current_block = match checkpoints.load(&chain_name).await? {
+            Some(last_block) => {
+                info!("Resuming {} from checkpoint {}", chain_name, last_block);
+                last_block + 1
             }
+            None => scanner.get_start_block(),
+        };
+
+        tokio::spawn(rescan_failed_blocks(
+            scanner.clone(),
+            failed_blocks.clone(),
+            sinks.clone(),
+            settings.failed_block_retry,
+        ));
+        tokio::spawn(async move {
+            run_scanner(
+                scanner,
+                checkpoints,
+                failed_blocks,
+                sinks,
+                current_block,
+                settings,
+                tx,
+            )
+            .await;
         });
     }
 
@@ -55,31 +126,339 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// 首次加载监控地址，并在后台按 reload_seconds 定期从文件和数据库刷新
+async fn start_watchlist(watchlist: Arc<Watchlist>, store: WatchlistStore) -> Result<(), AppError> {
+    if !watchlist.is_enabled() {
+        return Ok(());
+    }
+
+    let count = watchlist.reload(&store).await?;
+    info!("Loaded {} watched {} addresses", count, watchlist.chain());
+
+    let reload_interval = Duration::from_secs(watchlist.reload_seconds().max(1));
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(reload_interval).await;
+            if let Err(e) = watchlist.reload(&store).await {
+                error!("Error reloading {} watchlist: {:?}", watchlist.chain(), e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// 单条链的调度参数
+struct ScanSettings {
+    interval: Duration,
+    max_backoff: Duration,
+    concurrency: usize,
+    confirmations: u64,
+    max_block_attempts: u32,
+    failed_block_retry: Duration,
+}
+
+impl ScanSettings {
+    fn new(scheduler: &SchedulerConfig, interval_seconds: Option<u64>, confirmations: u64) -> Self {
+        let interval = Duration::from_secs(interval_seconds.unwrap_or(scheduler.interval_seconds));
+        ScanSettings {
+            interval,
+            max_backoff: Duration::from_secs(scheduler.max_backoff_seconds).max(interval),
+            concurrency: scheduler.catch_up_concurrency.max(1),
+            confirmations,
+            max_block_attempts: scheduler.max_block_attempts.max(1),
+            failed_block_retry: Duration::from_secs(scheduler.failed_block_retry_seconds.max(1)),
+        }
+    }
+}
+
+/// 单条链的扫块循环：落后于链头时按批并发追块，追上后按调度间隔轮询。
+///
+/// 处理失败的区块原地重试，连续失败 max_block_attempts 次后写入死信列表，
+/// 断点只会越过已完整处理或已写入死信列表的区块。
+async fn run_scanner(
+    scanner: Arc<dyn BlockchainScanner>,
+    checkpoints: CheckpointStore,
+    failed_blocks: FailedBlockStore,
+    sinks: Arc<Vec<Box<dyn TransactionSink>>>,
+    mut current_block: u64,
+    settings: ScanSettings,
+    tx: mpsc::Sender<()>,
+) {
+    let chain_name = scanner.chain_name().to_string();
+    let concurrency = settings.concurrency;
+    // 连续没有新区块时等待时间逐步翻倍，直到 max_backoff
+    let mut idle_delay = settings.interval;
+    // 当前区块连续处理失败的次数
+    let mut block_failures = 0u32;
+
+    loop {
+        let latest_block = match scanner.get_latest_block().await {
+            Ok(latest_block) => latest_block,
+            Err(e) => {
+                error!("Error fetching {} latest block: {:?}", chain_name, e);
+                tokio::time::sleep(settings.interval).await;
+                continue;
+            }
+        };
+
+        // 只处理确认数已满足 latest_block - block_num >= confirmations 的区块
+        let safe_block = match latest_block.checked_sub(settings.confirmations) {
+            Some(safe_block) if current_block <= safe_block => safe_block,
+            _ => {
+                tokio::time::sleep(idle_delay).await;
+                idle_delay = (idle_delay * 2).min(settings.max_backoff);
+                continue;
+            }
+        };
+        idle_delay = settings.interval;
+
+        let end_block = safe_block.min(current_block + concurrency as u64 - 1);
+        if safe_block - current_block >= concurrency as u64 {
+            info!(
+                "{} is {} blocks behind, catching up",
+                chain_name,
+                safe_block - current_block
+            );
+        }
+
+        // 区块并发拉取；buffered 保证结果按区块顺序返回，链重组检查和断点都顺序推进
+        let mut results = stream::iter(current_block..=end_block)
+            .map(|block_num| {
+                let scanner = &*scanner;
+                let chain_name = chain_name.as_str();
+                async move { (block_num, scan_block(scanner, block_num, chain_name).await) }
+            })
+            .buffered(concurrency);
+
+        let mut failed = false;
+        while let Some((block_num, result)) = results.next().await {
+            if let Err(e) =
+                process_block(&*scanner, &sinks, &chain_name, latest_block, result).await
+            {
+                block_failures += 1;
+                error!(
+                    "Error processing {} block {} (attempt {}): {}",
+                    chain_name, block_num, block_failures, e
+                );
+                if block_failures < settings.max_block_attempts {
+                    failed = true;
+                    break;
+                }
+
+                // 原地重试多次仍失败，写入死信列表交给后台任务，之后才越过该区块
+                let next_attempt_at = chrono::Utc::now().timestamp_millis()
+                    + settings.failed_block_retry.as_millis() as i64;
+                if let Err(db_error) = failed_blocks
+                    .add(&chain_name, block_num, block_failures, next_attempt_at, &e)
+                    .await
+                {
+                    error!(
+                        "Error recording failed {} block {}: {:?}",
+                        chain_name, block_num, db_error
+                    );
+                    failed = true;
+                    break;
+                }
+                warn!(
+                    "Moved {} block {} to the failed block list after {} attempts",
+                    chain_name, block_num, block_failures
+                );
+            }
+
+            block_failures = 0;
+            current_block = block_num + 1;
+            if let Err(e) = checkpoints.save(&chain_name, block_num).await {
+                error!("Error saving {} checkpoint: {:?}", chain_name, e);
+            }
+        }
+        drop(results);
+
+        if failed {
+            // 下一轮从失败的区块重新开始，等待时间随失败次数翻倍
+            let delay = settings
+                .interval
+                .saturating_mul(2u32.saturating_pow(block_failures - 1))
+                .min(settings.max_backoff);
+            tokio::time::sleep(delay).await;
+        }
+
+        if tx.send(()).await.is_err() {
+            break;
+        }
+    }
+}
+
 #[instrument(skip(scanner))]
 async fn scan_block(
     scanner: &dyn BlockchainScanner,
     block_num: u64,
     chain_name: &str,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+) -> Result<ScannedBlock, AppError> {
     info!("Scanning {} block: {}", chain_name, block_num);
+    scanner.scan_block(block_num).await
+}
 
-    match scanner.scan_block(block_num).await {
-        Ok(transactions) => {
-            let filtered_transactions = scanner.filter_transactions(transactions).await;
-            if !filtered_transactions.is_empty() {
-                info!(
-                    "Relevant transactions for {} block {}:",
-                    chain_name, block_num
-                );
-                for tx in filtered_transactions {
-                    println!("{}", serde_json::to_string_pretty(&tx).unwrap());
-                }
+/// 按区块顺序检查链重组并把事件发送给所有 sink，返回错误说明该区块没有完整处理。
+///
+/// 链重组的状态只在投递成功后提交：投递失败时重试同一区块会重新检测到链重组，
+/// revert 和重新扫描的事件不会丢失；同一批中之后的区块也尚未提交，会在下一轮重新处理
+async fn process_block(
+    scanner: &dyn BlockchainScanner,
+    sinks: &[Box<dyn TransactionSink>],
+    chain_name: &str,
+    latest_block: u64,
+    result: Result<ScannedBlock, AppError>,
+) -> Result<(), String> {
+    let block = result.map_err(|e| e.to_string())?;
+    let block_num = block.number;
+    let reconciled = scanner
+        .reconcile_block(block)
+        .await
+        .map_err(|e| e.to_string())?;
+    deliver_block(
+        scanner,
+        sinks,
+        chain_name,
+        block_num,
+        latest_block,
+        reconciled.events.clone(),
+    )
+    .await?;
+    scanner.commit_block(&reconciled).await;
+    Ok(())
+}
+
+/// 过滤并补充交易信息，填写确认数后发送给所有 sink
+async fn deliver_block(
+    scanner: &dyn BlockchainScanner,
+    sinks: &[Box<dyn TransactionSink>],
+    chain_name: &str,
+    block_num: u64,
+    latest_block: u64,
+    transactions: Vec<ScannedTransaction>,
+) -> Result<(), String> {
+    let mut transactions = scanner.filter_transactions(transactions).await;
+    scanner.enrich_transactions(&mut transactions).await;
+    for tx in transactions.iter_mut() {
+        tx.confirmations = latest_block.saturating_sub(tx.block_number);
+    }
+    emit_transactions(sinks, chain_name, block_num, &transactions)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 后台定期重新扫描死信列表中到期的区块，成功后从列表中删除。
+///
+/// 死信区块通常已落后于链头，不做链重组检查也不写入扫描器的重组记录，
+/// 以免与实时扫描的区块混在一起
+async fn rescan_failed_blocks(
+    scanner: Arc<dyn BlockchainScanner>,
+    failed_blocks: FailedBlockStore,
+    sinks: Arc<Vec<Box<dyn TransactionSink>>>,
+    retry_interval: Duration,
+) {
+    let chain_name = scanner.chain_name().to_string();
+
+    loop {
+        tokio::time::sleep(retry_interval).await;
+
+        let due = match failed_blocks
+            .due(&chain_name, FAILED_BLOCK_BATCH_SIZE)
+            .await
+        {
+            Ok(due) => due,
+            Err(e) => {
+                error!("Error reading {} failed blocks: {:?}", chain_name, e);
+                continue;
             }
+        };
+        if due.is_empty() {
+            continue;
         }
-        Err(e) => {
-            error!("Error scanning {} block {}: {:?}", chain_name, block_num, e);
+
+        let latest_block = match scanner.get_latest_block().await {
+            Ok(latest_block) => latest_block,
+            Err(e) => {
+                error!("Error fetching {} latest block: {:?}", chain_name, e);
+                continue;
+            }
+        };
+
+        for block in due {
+            let result = match scan_block(&*scanner, block.block_number, &chain_name).await {
+                Ok(scanned) => {
+                    deliver_block(
+                        &*scanner,
+                        &sinks,
+                        &chain_name,
+                        block.block_number,
+                        latest_block,
+                        scanned.transactions,
+                    )
+                    .await
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            let update = match result {
+                Ok(()) => {
+                    info!(
+                        "Rescanned failed {} block {}",
+                        chain_name, block.block_number
+                    );
+                    failed_blocks.delete(&chain_name, block.block_number).await
+                }
+                Err(e) => {
+                    let attempts = block.attempts + 1;
+                    warn!(
+                        "Rescanning failed {} block {} failed again (attempt {}): {}",
+                        chain_name, block.block_number, attempts, e
+                    );
+                    let next_attempt_at =
+                        chrono::Utc::now().timestamp_millis() + retry_interval.as_millis() as i64;
+                    failed_blocks
+                        .record_failure(
+                            &chain_name,
+                            block.block_number,
+                            attempts,
+                            next_attempt_at,
+                            &e,
+                        )
+                        .await
+                }
+            };
+
+            if let Err(e) = update {
+                error!("Error updating {} failed blocks: {:?}", chain_name, e);
+            }
         }
     }
+}
 
-    Ok(())
+/// 把一个区块的相关交易发送给所有 sink，任意一个失败都返回错误
+async fn emit_transactions(
+    sinks: &[Box<dyn TransactionSink>],
+    chain_name: &str,
+    block_num: u64,
+    transactions: &[ScannedTransaction],
+) -> Result<(), AppError> {
+    if transactions.is_empty() {
+        return Ok(());
+    }
+
+    info!(
+        "{} relevant transactions for {} block {}",
+        transactions.len(),
+        chain_name,
+        block_num
+    );
+
+    let mut result = Ok(());
+    for sink in sinks {
+        if let Err(e) = sink.send(transactions).await {
+            error!("Error sending to {} sink: {:?}", sink.name(), e);
+            result = Err(e);
+        }
+    }
+    result
 }
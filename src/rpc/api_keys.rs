@@ -0,0 +1,109 @@
+use crate::rpc::rate_limit::TokenBucket;
+use crate::utils::error::AppError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+struct ApiKey {
+    value: String,
+    limiter: TokenBucket,
+    /// 服务端提示超出频率或额度后暂停使用到该时间
+    suspended_until: Mutex<Option<Instant>>,
+}
+
+/// 轮流使用的一组 API key，每个 key 有独立的令牌桶，被服务端限流的 key 暂停一段时间
+pub struct ApiKeyRing {
+    keys: Vec<ApiKey>,
+    cursor: AtomicUsize,
+}
+
+impl ApiKeyRing {
+    pub fn new(keys: &[String], requests_per_second: f64, burst: u32) -> Self {
+        ApiKeyRing {
+            keys: keys
+                .iter()
+                .map(|key| ApiKey {
+                    value: key.clone(),
+                    limiter: TokenBucket::new(requests_per_second, burst),
+                    suspended_until: Mutex::new(None),
+                })
+                .collect(),
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn key(&self, index: usize) -> &str {
+        &self.keys[index].value
+    }
+
+    /// 选出下一个未暂停的 key 并等待它的令牌，所有 key 都暂停时返回 QuotaExceeded
+    pub async fn acquire(&self) -> Result<usize, AppError> {
+        let now = Instant::now();
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+        let index = (0..self.keys.len())
+            .map(|offset| (start + offset) % self.keys.len())
+            .find(|&index| {
+                self.keys[index]
+                    .suspended_until
+                    .lock()
+                    .unwrap()
+                    .is_none_or(|until| until <= now)
+            });
+
+        let Some(index) = index else {
+            let resume_in = self
+                .keys
+                .iter()
+                .filter_map(|key| *key.suspended_until.lock().unwrap())
+                .min()
+                .map(|until| until.saturating_duration_since(now))
+                .unwrap_or_default();
+            return Err(AppError::QuotaExceeded(
+                resume_in,
+                format!("all {} API keys are suspended", self.keys.len()),
+            ));
+        };
+
+        self.keys[index].limiter.acquire().await;
+        Ok(index)
+    }
+
+    pub fn suspend(&self, index: usize, duration: Duration, reason: &str) {
+        warn!(
+            "API key {} suspended for {:?}: {}",
+            mask(&self.keys[index].value),
+            duration,
+            reason
+        );
+        *self.keys[index].suspended_until.lock().unwrap() = Some(Instant::now() + duration);
+    }
+}
+
+/// 日志中只显示 key 的前 4 个字符
+fn mask(key: &str) -> String {
+    format!("{}***", key.chars().take(4).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_when_the_next_key_resumes() {
+        let ring = ApiKeyRing::new(&["key-a".to_string(), "key-b".to_string()], 100.0, 1);
+        ring.suspend(0, Duration::from_secs(30), "exceeds");
+        ring.suspend(1, Duration::from_secs(10), "exceeds");
+
+        match ring.acquire().await {
+            Err(AppError::QuotaExceeded(resume_in, _)) => {
+                assert!(resume_in > Duration::from_secs(9) && resume_in <= Duration::from_secs(10))
+            }
+            other => panic!("expected QuotaExceeded, got {:?}", other),
+        }
+    }
+}
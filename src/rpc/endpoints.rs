@@ -0,0 +1,351 @@
+use crate::rpc::retry::RetryPolicy;
+use crate::utils::config::{EndpointConfig, LoadBalancing, RpcConfig};
+use crate::utils::error::AppError;
+use config::ConfigError;
+use futures::future::join_all;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+struct Endpoint {
+    url: String,
+    weight: usize,
+    /// 不健康时记录恢复时间，冷却结束前优先使用其他端点
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .is_none_or(|until| until <= now)
+    }
+}
+
+/// 单条链的一组 RPC 端点，负责负载均衡、健康跟踪和故障切换
+pub struct EndpointPool {
+    chain: String,
+    endpoints: Vec<Endpoint>,
+    strategy: LoadBalancing,
+    cooldown: Duration,
+    cross_check_latest: bool,
+    max_block_lag: u64,
+    cursor: AtomicUsize,
+    retry: RetryPolicy,
+}
+
+impl EndpointPool {
+    pub fn new(
+        chain: &str,
+        endpoints: &[EndpointConfig],
+        config: &RpcConfig,
+    ) -> Result<Self, AppError> {
+        if endpoints.is_empty() {
+            return Err(AppError::ConfigError(ConfigError::Message(format!(
+                "Chain {} has no RPC endpoint",
+                chain
+            ))));
+        }
+
+        Ok(EndpointPool {
+            chain: chain.to_string(),
+            endpoints: endpoints
+                .iter()
+                .map(|endpoint| Endpoint {
+                    url: endpoint.url.trim_end_matches('/').to_string(),
+                    weight: endpoint.weight.max(1) as usize,
+                    unhealthy_until: Mutex::new(None),
+                })
+                .collect(),
+            strategy: config.load_balancing,
+            cooldown: Duration::from_secs(config.cooldown_seconds),
+            cross_check_latest: config.cross_check_latest,
+            max_block_lag: config.max_block_lag,
+            cursor: AtomicUsize::new(0),
+            retry: RetryPolicy::new(&config.retry),
+        })
+    }
+
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.endpoints.iter().map(|endpoint| endpoint.url.as_str())
+    }
+
+    /// 按负载均衡策略选择端点发送请求，网络错误、5xx 和限流时切换到下一个端点；
+    /// 所有端点都失败后按重试策略等待并重新开始一轮
+    pub async fn call<T, F, Fut>(&self, request: F) -> Result<T, AppError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let label = format!("{} RPC request", self.chain);
+        self.retry.run(&label, || self.call_once(&request)).await
+    }
+
+    async fn call_once<T, F, Fut>(&self, request: &F) -> Result<T, AppError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let mut last_error = None;
+        for index in self.order() {
+            let endpoint = &self.endpoints[index];
+            match request(endpoint.url.clone()).await {
+                Ok(result) => {
+                    self.mark_healthy(endpoint);
+                    return Ok(result);
+                }
+                Err(e) if is_failover_error(&e) => {
+                    self.mark_unhealthy(endpoint, &e);
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    /// 获取最新区块号。开启交叉校验时同时询问所有健康端点，落后超过 max_block_lag
+    /// 的端点暂时标记为不健康，返回其余端点中最小的区块号，避免请求尚未同步到的区块
+    pub async fn latest_block<F, Fut>(&self, fetch: F) -> Result<u64, AppError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<u64, AppError>>,
+    {
+        if !self.cross_check_latest || self.endpoints.len() < 2 {
+            return self.call(fetch).await;
+        }
+
+        let now = Instant::now();
+        let candidates: Vec<&Endpoint> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_healthy(now))
+            .collect();
+        if candidates.len() < 2 {
+            return self.call(fetch).await;
+        }
+
+        let results = join_all(
+            candidates
+                .iter()
+                .map(|endpoint| fetch(endpoint.url.clone())),
+        )
+        .await;
+
+        let mut heights = Vec::new();
+        let mut last_error = None;
+        for (endpoint, result) in candidates.into_iter().zip(results) {
+            match result {
+                Ok(height) => {
+                    self.mark_healthy(endpoint);
+                    heights.push((endpoint, height));
+                }
+                Err(e) => {
+                    if is_failover_error(&e) {
+                        self.mark_unhealthy(endpoint, &e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let Some(highest) = heights.iter().map(|(_, height)| *height).max() else {
+            return Err(last_error.unwrap());
+        };
+
+        let mut in_sync = Vec::new();
+        for (endpoint, height) in heights {
+            if highest - height > self.max_block_lag {
+                let e = AppError::ApiError(format!(
+                    "latest block {} is {} blocks behind",
+                    height,
+                    highest - height
+                ));
+                self.mark_unhealthy(endpoint, &e);
+            } else {
+                in_sync.push(height);
+            }
+        }
+
+        Ok(in_sync.into_iter().min().unwrap_or(highest))
+    }
+
+    /// 本次请求依次尝试的端点：健康端点按策略排序在前，不健康的端点排在最后作为兜底
+    fn order(&self) -> Vec<usize> {
+        let count = self.endpoints.len();
+        let start = match self.strategy {
+            LoadBalancing::Failover => 0,
+            LoadBalancing::RoundRobin => self.cursor.fetch_add(1, Ordering::Relaxed) % count,
+            LoadBalancing::Weighted => {
+                // 按权重展开的轮询：权重为 2 的端点在每轮中被选中两次
+                let total: usize = self.endpoints.iter().map(|e| e.weight).sum();
+                let mut slot = self.cursor.fetch_add(1, Ordering::Relaxed) % total;
+                self.endpoints
+                    .iter()
+                    .position(|endpoint| {
+                        if slot < endpoint.weight {
+                            true
+                        } else {
+                            slot -= endpoint.weight;
+                            false
+                        }
+                    })
+                    .unwrap_or(0)
+            }
+        };
+
+        let now = Instant::now();
+        let (healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..count)
+            .map(|offset| (start + offset) % count)
+            .partition(|&index| self.endpoints[index].is_healthy(now));
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+
+    fn mark_healthy(&self, endpoint: &Endpoint) {
+        if endpoint.unhealthy_until.lock().unwrap().take().is_some() {
+            info!("{} RPC endpoint {} recovered", self.chain, endpoint.url);
+        }
+    }
+
+    fn mark_unhealthy(&self, endpoint: &Endpoint, error: &AppError) {
+        warn!(
+            "{} RPC endpoint {} failed, cooling down for {:?}: {}",
+            self.chain, endpoint.url, self.cooldown, error
+        );
+        *endpoint.unhealthy_until.lock().unwrap() = Some(Instant::now() + self.cooldown);
+    }
+}
+
+/// 网络错误、5xx 和 429 限流说明端点本身有问题，可以换一个端点重试
+pub fn is_failover_error(error: &AppError) -> bool {
+    match error {
+        AppError::NetworkError(_) => true,
+        AppError::HttpError(status, _) => *status >= 500,
+        AppError::RateLimited(_, _) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::RetryConfig;
+
+    fn pool(weights: &[u32], strategy: LoadBalancing, cross_check_latest: bool) -> EndpointPool {
+        let endpoints: Vec<EndpointConfig> = weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| EndpointConfig {
+                url: format!("http://node{}", index),
+                weight: *weight,
+            })
+            .collect();
+        let config = RpcConfig {
+            load_balancing: strategy,
+            cross_check_latest,
+            max_block_lag: 5,
+            // 只测试一轮端点切换，不等待重试
+            retry: RetryConfig {
+                max_attempts: 1,
+                ..RetryConfig::default()
+            },
+            ..RpcConfig::default()
+        };
+        EndpointPool::new("test", &endpoints, &config).unwrap()
+    }
+
+    /// 按 URL 返回结果的请求，同时记录请求过的端点
+    async fn call_with(
+        pool: &EndpointPool,
+        calls: &Mutex<Vec<String>>,
+        respond: impl Fn(&str) -> Result<u64, AppError>,
+    ) -> Result<u64, AppError> {
+        let respond = &respond;
+        pool.call(|url| async move {
+            calls.lock().unwrap().push(url.clone());
+            respond(&url)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn weighted_selection_follows_weights() {
+        let pool = pool(&[2, 1], LoadBalancing::Weighted, false);
+        let calls = Mutex::new(Vec::new());
+        for _ in 0..3 {
+            call_with(&pool, &calls, |_| Ok(1)).await.unwrap();
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["http://node0", "http://node0", "http://node1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_server_errors_and_rate_limits() {
+        for error in [
+            AppError::HttpError(503, "unavailable".to_string()),
+            AppError::RateLimited(None, "too many requests".to_string()),
+            AppError::NetworkError("connection refused".to_string()),
+        ] {
+            let pool = pool(&[1, 1], LoadBalancing::Failover, false);
+            let calls = Mutex::new(Vec::new());
+            let error = Mutex::new(Some(error));
+            let result = call_with(&pool, &calls, |url| match url {
+                "http://node0" => Err(error.lock().unwrap().take().unwrap()),
+                _ => Ok(7),
+            })
+            .await;
+            assert_eq!(result.unwrap(), 7);
+            assert_eq!(*calls.lock().unwrap(), vec!["http://node0", "http://node1"]);
+
+            // 冷却中的端点排在最后，只作为兜底
+            assert_eq!(pool.order(), vec![1, 0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn api_errors_do_not_fail_over() {
+        let pool = pool(&[1, 1], LoadBalancing::Failover, false);
+        let calls = Mutex::new(Vec::new());
+        let result = call_with(&pool, &calls, |_| {
+            Err(AppError::ApiError("execution reverted".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::ApiError(_))));
+        assert_eq!(*calls.lock().unwrap(), vec!["http://node0"]);
+        assert_eq!(pool.order(), vec![0, 1]);
+    }
+
+    #[test]
+    fn classifies_failover_errors() {
+        assert!(is_failover_error(&AppError::NetworkError(String::new())));
+        assert!(is_failover_error(&AppError::HttpError(500, String::new())));
+        assert!(is_failover_error(&AppError::RateLimited(
+            None,
+            String::new()
+        )));
+        assert!(!is_failover_error(&AppError::HttpError(404, String::new())));
+        assert!(!is_failover_error(&AppError::ApiError(String::new())));
+        assert!(!is_failover_error(&AppError::JsonParseError(String::new())));
+    }
+
+    #[tokio::test]
+    async fn cross_check_returns_lowest_in_sync_height() {
+        let pool = pool(&[1, 1, 1], LoadBalancing::Failover, true);
+        let heights = |url: String| async move {
+            match url.as_str() {
+                "http://node0" => Ok(90),
+                "http://node1" => Ok(100),
+                _ => Ok(97),
+            }
+        };
+        assert_eq!(pool.latest_block(heights).await.unwrap(), 97);
+
+        // 落后超过 max_block_lag 的端点进入冷却
+        assert_eq!(pool.order(), vec![1, 2, 0]);
+    }
+}
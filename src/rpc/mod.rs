@@ -0,0 +1,100 @@
+pub mod api_keys;
+pub mod endpoints;
+pub mod rate_limit;
+pub mod retry;
+
+use crate::utils::config::HttpConfig;
+use crate::utils::error::AppError;
+use config::ConfigError;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use reqwest::{Certificate, Client, Proxy, RequestBuilder, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+/// 按链的 http 配置创建 HTTP 客户端，配置错误在启动时直接返回
+pub fn build_client(chain: &str, config: &HttpConfig) -> Result<Client, AppError> {
+    let invalid = |what: &str, e: &dyn std::fmt::Display| {
+        AppError::ConfigError(ConfigError::Message(format!(
+            "Invalid {} for chain {}: {}",
+            what, chain, e
+        )))
+    };
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name =
+            HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid("header name", &e))?;
+        let mut value = HeaderValue::from_str(value).map_err(|e| invalid("header value", &e))?;
+        // 请求头中常有 API key，避免出现在调试输出中
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout_seconds))
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_seconds))
+        .default_headers(headers)
+        .danger_accept_invalid_certs(config.accept_invalid_certs);
+
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Proxy::all(proxy).map_err(|e| invalid("proxy", &e))?);
+    }
+
+    if let Some(path) = &config.ca_certificate {
+        let pem = std::fs::read(path)?;
+        let certificate = Certificate::from_pem(&pem).map_err(|e| invalid("CA certificate", &e))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder
+        .build()
+        .map_err(|e| AppError::NetworkError(e.to_string()))
+}
+
+/// POST JSON 请求体并解析 JSON 响应。
+///
+/// 429 返回带 Retry-After 的 RateLimited，其他非 2xx 状态返回 HttpError，
+/// 由端点池和重试策略据此判断是否切换端点或重试。
+pub async fn post_json(client: &Client, url: &str, body: &Value) -> Result<Value, AppError> {
+    send_json(client.post(url).json(body)).await
+}
+
+/// 发送已构造好的请求并解析 JSON 响应，错误分类与 post_json 相同
+pub async fn send_json(request: RequestBuilder) -> Result<Value, AppError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::NetworkError(e.to_string()))?;
+
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AppError::NetworkError(e.to_string()))?;
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(AppError::RateLimited(retry_after, body));
+    }
+    if !status.is_success() {
+        return Err(AppError::HttpError(status.as_u16(), body));
+    }
+
+    serde_json::from_str(&body).map_err(|e| AppError::JsonParseError(e.to_string()))
+}
+
+/// Retry-After 可以是秒数，也可以是 HTTP 日期
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let seconds = (date.timestamp() - chrono::Utc::now().timestamp()).max(0);
+    Some(Duration::from_secs(seconds as u64))
+}
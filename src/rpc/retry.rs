@@ -0,0 +1,139 @@
+use crate::utils::config::{RetryConfig, RetryableError};
+use crate::utils::error::AppError;
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// 两个扫描器共用的 RPC 重试策略：指数退避、随机抖动，限流时遵循 Retry-After
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+    retry_on: Vec<RetryableError>,
+}
+
+impl RetryPolicy {
+    pub fn new(config: &RetryConfig) -> Self {
+        RetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+            jitter: config.jitter.clamp(0.0, 1.0),
+            retry_on: config.retry_on.clone(),
+        }
+    }
+
+    /// 执行 operation，失败且错误属于可重试类别时等待后重试，最多 max_attempts 次
+    pub async fn run<T, F, Fut>(&self, label: &str, mut operation: F) -> Result<T, AppError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(result) => return Ok(result),
+                Err(e) if attempt < self.max_attempts && self.is_retryable(&e) => {
+                    let delay = self.delay(attempt, &e);
+                    warn!(
+                        "{} failed (attempt {}/{}), retrying in {:?}: {}",
+                        label, attempt, self.max_attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn is_retryable(&self, error: &AppError) -> bool {
+        let class = match error {
+            AppError::NetworkError(_) => RetryableError::Network,
+            AppError::HttpError(status, _) if *status >= 500 => RetryableError::ServerError,
+            AppError::RateLimited(_, _)
+            | AppError::ApiKeyRateLimited(_, _)
+            | AppError::QuotaExceeded(_, _) => RetryableError::RateLimited,
+            AppError::ApiError(_) => RetryableError::Api,
+            AppError::JsonParseError(_) => RetryableError::InvalidResponse,
+            _ => return false,
+        };
+        self.retry_on.contains(&class)
+    }
+
+    /// 第 n 次失败后等待 base_delay * 2^(n-1)，不超过 max_delay，并加上 ±jitter 比例的随机抖动；
+    /// 服务端给出 Retry-After 或所有 API key 都被暂停时，至少等到可以再次请求
+    fn delay(&self, attempt: u32, error: &AppError) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let backoff = if self.jitter > 0.0 {
+            let scale = rand::thread_rng().gen_range(1.0 - self.jitter..=1.0 + self.jitter);
+            backoff.mul_f64(scale)
+        } else {
+            backoff
+        };
+
+        match error {
+            AppError::RateLimited(Some(retry_after), _) => backoff.max(*retry_after),
+            AppError::QuotaExceeded(resume_in, _) => backoff.max(*resume_in),
+            _ => backoff,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(&RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+            jitter: 0.0,
+            retry_on: vec![RetryableError::RateLimited],
+        })
+    }
+
+    #[test]
+    fn waits_until_a_suspended_key_resumes() {
+        let error = AppError::QuotaExceeded(Duration::from_secs(30), "all keys".to_string());
+        assert_eq!(policy().delay(1, &error), Duration::from_secs(30));
+
+        let error = AppError::RateLimited(Some(Duration::from_secs(5)), String::new());
+        assert_eq!(policy().delay(1, &error), Duration::from_secs(5));
+        assert_eq!(
+            policy().delay(1, &AppError::RateLimited(None, String::new())),
+            Duration::from_millis(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_quota_errors_after_resume_time() {
+        let started = Instant::now();
+        let mut calls = 0;
+        let result = policy()
+            .run("test", || {
+                calls += 1;
+                let first = calls == 1;
+                async move {
+                    if first {
+                        Err(AppError::QuotaExceeded(
+                            Duration::from_millis(200),
+                            "all keys".to_string(),
+                        ))
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(calls, 2);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,1048 @@
+use crate::rpc::endpoints::EndpointPool;
+use crate::rpc::{build_client, post_json};
+use crate::scanners::types::{
+    ChainDetails, EvmDetails, ReconciledBlock, ScannedBlock, ScannedTransaction, TokenEventType,
+    TokenStandard, TokenTransfer, TransactionEvent, TransactionStatus,
+};
+use crate::scanners::watchlist::Watchlist;
+use crate::scanners::BlockchainScanner;
+use crate::tokens::{TokenMetadataSource, TokenRegistry, ViewMethod};
+use crate::utils::abi::decode_transfer_log;
+use crate::utils::config::{BlockFetchMode, ChainConfig, EvmOptions};
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use num_bigint::BigInt;
+use num_traits::Num;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// 同时进行的回执请求（或回执批量请求）数量
+const RECEIPT_CONCURRENCY: usize = 8;
+
+/// 通用的 EVM JSON-RPC 扫描器，BSC、Ethereum、Polygon、Arbitrum 等链按配置各自实例化
+pub struct EvmScanner {
+    chain_name: String,
+    chain_id: u64,
+    native_symbol: String,
+    endpoints: EndpointPool,
+    client: Client,
+    start_block: u64,
+    max_reorg_depth: u64,
+    fetch_mode: BlockFetchMode,
+    detected_fetch_mode: OnceLock<BlockFetchMode>,
+    /// 单个 JSON-RPC 批量请求中的最大调用数
+    batch_size: usize,
+    request_id: AtomicU64,
+    /// 已提交的最近区块，用于检测链重组并撤销孤块中的交易
+    recent_blocks: Mutex<BTreeMap<u64, ScannedBlock>>,
+    watchlist: Arc<Watchlist>,
+    tokens: Arc<TokenRegistry>,
+}
+
+fn receipts_array(receipts: Value) -> Result<Vec<Value>, AppError> {
+    match receipts {
+        Value::Array(receipts) => Ok(receipts),
+        _ => Err(AppError::ParseError("Failed to parse receipts".to_string())),
+    }
+}
+
+fn is_unsupported_method(error: &AppError) -> bool {
+    match error {
+        AppError::ApiError(_) => true,
+        AppError::HttpError(status, _) => (400..500).contains(status),
+        _ => false,
+    }
+}
+
+/// eth_call 被合约回滚时节点返回的错误，例如 "execution reverted"、
+/// "invalid opcode" 或 "VM execution error"
+fn is_revert_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("revert")
+        || message.contains("invalid opcode")
+        || message.contains("execution error")
+}
+
+impl EvmScanner {
+    pub fn new(
+        config: &ChainConfig,
+        options: &EvmOptions,
+        watchlist: Arc<Watchlist>,
+        tokens: Arc<TokenRegistry>,
+    ) -> Result<Self, AppError> {
+        Ok(EvmScanner {
+            chain_name: config.name.clone(),
+            chain_id: options.chain_id,
+            native_symbol: options.native_symbol.clone(),
+            endpoints: EndpointPool::new(&config.name, &config.all_endpoints(), &config.rpc)?,
+            client: build_client(&config.name, &config.http)?,
+            start_block: config.start_block,
+            max_reorg_depth: options.max_reorg_depth,
+            fetch_mode: options.block_fetch_mode,
+            detected_fetch_mode: OnceLock::new(),
+            batch_size: options.batch_size,
+            request_id: AtomicU64::new(1),
+            recent_blocks: Mutex::new(BTreeMap::new()),
+            watchlist,
+            tokens,
+        })
+    }
+
+    fn next_request_id(&self) -> u64 {
+        self.request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    async fn make_request(&self, method: &str, params: Value) -> Result<Value, AppError> {
+        let params = &params;
+        self.endpoints
+            .call(|url| async move { self.request_at(&url, method, params.clone()).await })
+            .await
+    }
+
+    /// 向指定端点发送单个 JSON-RPC 调用，不做故障切换
+    async fn request_at(&self, url: &str, method: &str, params: Value) -> Result<Value, AppError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_request_id(),
+            "method": method,
+            "params": params
+        });
+        let response = post_json(&self.client, url, &request).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(AppError::ApiError(error.to_string()));
+        }
+
+        Ok(response["result"].clone())
+    }
+
+    /// 在一个 HTTP 请求中发送多个 JSON-RPC 调用，按 id 把响应对应回调用顺序。
+    ///
+    /// 外层 Err 表示整个批次失败（网络错误或节点不支持批量请求），
+    /// 内层 Err 是单个调用返回的错误或缺失的响应。
+    async fn make_batch_request(
+        &self,
+        calls: &[(&str, Value)],
+    ) -> Result<Vec<Result<Value, AppError>>, AppError> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // 一次性预留整批 id，保证连续且不与并发请求冲突
+        let first_id = self
+            .request_id
+            .fetch_add(calls.len() as u64, Ordering::Relaxed);
+        let batch = calls
+            .iter()
+            .enumerate()
+            .map(|(offset, (method, params))| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": first_id + offset as u64,
+                    "method": method,
+                    "params": params
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let batch = &Value::Array(batch);
+        let response = self
+            .endpoints
+            .call(|url| async move { post_json(&self.client, &url, batch).await })
+            .await?;
+
+        // 不支持批量请求的节点通常返回单个错误对象
+        let Value::Array(items) = response else {
+            return Err(AppError::ApiError(format!(
+                "Batch request rejected: {}",
+                response.get("error").unwrap_or(&response)
+            )));
+        };
+
+        let mut by_id: HashMap<u64, Value> = items
+            .into_iter()
+            .filter_map(|item| item["id"].as_u64().map(|id| (id, item)))
+            .collect();
+
+        Ok((0..calls.len())
+            .map(|offset| {
+                let id = first_id + offset as u64;
+                let mut item = by_id.remove(&id).ok_or_else(|| {
+                    AppError::ApiError(format!(
+                        "Missing response for {} (id {})",
+                        calls[offset].0, id
+                    ))
+                })?;
+                if let Some(error) = item.get("error") {
+                    return Err(AppError::ApiError(error.to_string()));
+                }
+                Ok(item["result"].take())
+            })
+            .collect())
+    }
+
+    /// 批量拉取交易回执，batch_size 不大于 1 时逐笔请求
+    async fn fetch_receipts(&self, hashes: Vec<String>) -> Result<Vec<Value>, AppError> {
+        let receipts: Vec<Value> = if self.batch_size <= 1 {
+            stream::iter(hashes.clone())
+                .map(|hash| async move {
+                    self.make_request("eth_getTransactionReceipt", json!([hash]))
+                        .await
+                })
+                .buffered(RECEIPT_CONCURRENCY)
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<_, _>>()?
+        } else {
+            let chunks: Vec<Vec<String>> = hashes
+                .chunks(self.batch_size)
+                .map(|chunk| chunk.to_vec())
+                .collect();
+            let batches = stream::iter(chunks)
+                .map(|chunk| async move {
+                    let calls: Vec<(&str, Value)> = chunk
+                        .iter()
+                        .map(|hash| ("eth_getTransactionReceipt", json!([hash])))
+                        .collect();
+                    self.make_batch_request(&calls).await
+                })
+                .buffered(RECEIPT_CONCURRENCY)
+                .collect::<Vec<_>>()
+                .await;
+
+            let mut receipts = Vec::with_capacity(hashes.len());
+            for batch in batches {
+                for receipt in batch? {
+                    receipts.push(receipt?);
+                }
+            }
+            receipts
+        };
+
+        // 节点尚未索引完回执时会返回 null，此时整个区块稍后重试
+        if let Some(position) = receipts.iter().position(|receipt| receipt.is_null()) {
+            return Err(AppError::ParseError(format!(
+                "Missing receipt for transaction {}",
+                hashes[position]
+            )));
+        }
+        Ok(receipts)
+    }
+
+    /// 按配置或自动探测到的方式拉取区块（含完整交易）及其全部回执
+    async fn fetch_block_with_receipts(
+        &self,
+        block_num: u64,
+    ) -> Result<(Value, Vec<Value>), AppError> {
+        if let Some(mode) = self.detected_fetch_mode.get() {
+            return self.fetch_with_mode(*mode, block_num).await;
+        }
+
+        let candidates = match self.fetch_mode {
+            BlockFetchMode::Auto => vec![
+                BlockFetchMode::BlockReceipts,
+                BlockFetchMode::QuickNode,
+                BlockFetchMode::TransactionReceipts,
+            ],
+            mode => vec![mode],
+        };
+
+        // 依次尝试，节点不支持的方法会返回 JSON-RPC 错误或 4xx，第一个成功的方式会被记住
+        let mut last_error = None;
+        for mode in candidates {
+            match self.fetch_with_mode(mode, block_num).await {
+                Ok(result) => {
+                    if self.detected_fetch_mode.set(mode).is_ok() {
+                        info!("{} block fetch mode: {:?}", self.chain_name, mode);
+                    }
+                    return Ok(result);
+                }
+                Err(e) if is_unsupported_method(&e) => {
+                    warn!(
+                        "{} block fetch mode {:?} unavailable: {}",
+                        self.chain_name, mode, e
+                    );
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| AppError::ApiError("No block fetch mode available".to_string())))
+    }
+
+    async fn fetch_with_mode(
+        &self,
+        mode: BlockFetchMode,
+        block_num: u64,
+    ) -> Result<(Value, Vec<Value>), AppError> {
+        let block_hex = format!("0x{:X}", block_num);
+
+        match mode {
+            BlockFetchMode::QuickNode => {
+                let mut result = self
+                    .make_request("qn_getBlockWithReceipts", json!([block_hex]))
+                    .await?;
+                let receipts = receipts_array(result["receipts"].take())?;
+                Ok((result["block"].take(), receipts))
+            }
+            BlockFetchMode::BlockReceipts => {
+                // 区块和回执放在同一个批量请求中，batch_size 不大于 1 时分别请求
+                let (block, receipts) = if self.batch_size <= 1 {
+                    tokio::try_join!(
+                        self.make_request("eth_getBlockByNumber", json!([block_hex, true])),
+                        self.make_request("eth_getBlockReceipts", json!([block_hex])),
+                    )?
+                } else {
+                    let mut results = self
+                        .make_batch_request(&[
+                            ("eth_getBlockByNumber", json!([block_hex, true])),
+                            ("eth_getBlockReceipts", json!([block_hex])),
+                        ])
+                        .await?
+                        .into_iter();
+                    (results.next().unwrap()?, results.next().unwrap()?)
+                };
+                if block.is_null() {
+                    return Err(AppError::ParseError(format!(
+                        "Block {} not found",
+                        block_hex
+                    )));
+                }
+                Ok((block, receipts_array(receipts)?))
+            }
+            BlockFetchMode::TransactionReceipts | BlockFetchMode::Auto => {
+                let block = self.fetch_full_block(&block_hex).await?;
+                let hashes: Vec<String> = block["transactions"]
+                    .as_array()
+                    .map(|txs| {
+                        txs.iter()
+                            .filter_map(|tx| tx["hash"].as_str().map(|h| h.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let receipts = self.fetch_receipts(hashes).await?;
+                Ok((block, receipts))
+            }
+        }
+    }
+
+    async fn fetch_full_block(&self, block_hex: &str) -> Result<Value, AppError> {
+        let block = self
+            .make_request("eth_getBlockByNumber", json!([block_hex, true]))
+            .await?;
+        if block.is_null() {
+            return Err(AppError::ParseError(format!(
+                "Block {} not found",
+                block_hex
+            )));
+        }
+        Ok(block)
+    }
+
+    /// 拉取区块及其回执，返回区块头中的哈希信息和解析后的交易
+    async fn fetch_block(&self, block_num: u64) -> Result<ScannedBlock, AppError> {
+        let (block, receipts) = self.fetch_block_with_receipts(block_num).await?;
+
+        // 检查区块是否为对象
+        let block = block
+            .as_object()
+            .ok_or_else(|| AppError::ParseError("Failed to parse block".to_string()))?;
+
+        let hash = block
+            .get("hash")
+            .and_then(|h| h.as_str())
+            .ok_or_else(|| AppError::ParseError("Missing block hash".to_string()))?;
+
+        let parent_hash = block
+            .get("parentHash")
+            .and_then(|h| h.as_str())
+            .ok_or_else(|| AppError::ParseError("Missing parent hash".to_string()))?;
+
+        let transactions = block
+            .get("transactions")
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| AppError::ParseError("Failed to parse transactions".to_string()))?;
+
+        // 确保 transactions 和 receipts 数量匹配
+        if transactions.len() != receipts.len() {
+            return Err(AppError::ParseError(
+                "Mismatch between transactions and receipts".to_string(),
+            ));
+        }
+
+        // 逐个解析交易
+        let mut parsed_transactions = Vec::new();
+        for (transaction, receipt) in transactions.iter().zip(receipts.iter()) {
+            let parsed_tx = self.parse_transaction(transaction, receipt).await?;
+            parsed_transactions.push(parsed_tx);
+        }
+
+        Ok(ScannedBlock {
+            number: block_num,
+            hash: hash.to_string(),
+            parent_hash: parent_hash.to_string(),
+            transactions: parsed_transactions,
+        })
+    }
+
+    async fn fetch_block_hash(&self, block_num: u64) -> Result<String, AppError> {
+        let block_hex = format!("0x{:X}", block_num);
+        let result = self
+            .make_request("eth_getBlockByNumber", json!([block_hex, false]))
+            .await?;
+
+        result
+            .get("hash")
+            .and_then(|h| h.as_str())
+            .map(|h| h.to_string())
+            .ok_or_else(|| AppError::ParseError(format!("Missing hash of block {}", block_num)))
+    }
+
+    /// 从 from_block 开始向前逐个比对已提交的哈希与节点当前的规范链哈希，
+    /// 按区块号从高到低返回孤块。只读取 recent_blocks，不做修改
+    async fn find_orphaned(
+        &self,
+        block_num: u64,
+        from_block: u64,
+        recent_blocks: &BTreeMap<u64, ScannedBlock>,
+    ) -> Result<Vec<u64>, AppError> {
+        let mut orphaned = Vec::new();
+        let mut fork_block = None;
+
+        for (&recorded_num, recorded) in recent_blocks.range(..=from_block).rev() {
+            if self.fetch_block_hash(recorded_num).await? == recorded.hash {
+                fork_block = Some(recorded_num);
+                break;
+            }
+            warn!(
+                "{} block {} ({}) was orphaned by a reorg",
+                self.chain_name, recorded_num, recorded.hash
+            );
+            orphaned.push(recorded_num);
+        }
+
+        match fork_block {
+            Some(fork_block) => info!(
+                "{} reorg detected at block {}, fork point is block {}",
+                self.chain_name, block_num, fork_block
+            ),
+            None => warn!(
+                "{} reorg at block {} is deeper than {} blocks",
+                self.chain_name, block_num, self.max_reorg_depth
+            ),
+        }
+
+        Ok(orphaned)
+    }
+}
+
+#[async_trait]
+impl TokenMetadataSource for EvmScanner {
+    async fn call_view(
+        &self,
+        contract_address: &str,
+        method: &ViewMethod,
+    ) -> Result<String, AppError> {
+        let call = json!({
+            "to": contract_address,
+            "data": format!("0x{}", method.selector)
+        });
+        let result = self
+            .make_request("eth_call", json!([call, "latest"]))
+            .await
+            .map_err(|e| match e {
+                AppError::ApiError(message) if is_revert_error(&message) => {
+                    AppError::ContractReverted(message)
+                }
+                e => e,
+            })?;
+
+        result
+            .as_str()
+            .map(|r| r.to_string())
+            .ok_or_else(|| AppError::ParseError("eth_call result is not a string".to_string()))
+    }
+}
+
+#[async_trait]
+impl BlockchainScanner for EvmScanner {
+    fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
+    async fn get_latest_block(&self) -> Result<u64, AppError> {
+        self.endpoints
+            .latest_block(|url| async move {
+                let result = self.request_at(&url, "eth_blockNumber", json!([])).await?;
+                let block_number = u64::from_str_radix(
+                    result.as_str().unwrap_or_default().trim_start_matches("0x"),
+                    16,
+                )
+                .map_err(|e| {
+                    AppError::ParseError(format!("Failed to parse block number: {}", e))
+                })?;
+                Ok(block_number)
+            })
+            .await
+    }
+
+    async fn scan_block(&self, block_num: u64) -> Result<ScannedBlock, AppError> {
+        self.fetch_block(block_num).await
+    }
+
+    async fn reconcile_block(&self, block: ScannedBlock) -> Result<ReconciledBlock, AppError> {
+        // 持锁只是为了读取一致的已提交状态；所有请求成功之前不做任何修改，
+        // 中途失败时重试同一区块会再次检测到同样的链重组
+        let recent_blocks = self.recent_blocks.lock().await;
+
+        let Some((&last_num, last)) = recent_blocks.range(..block.number).next_back() else {
+            return Ok(ReconciledBlock::new(block));
+        };
+
+        // 父区块已提交时直接比对 parentHash；中间有空缺（例如父区块进了死信列表）时，
+        // 向节点核对最近提交的区块，而不是当作没有链重组
+        let reorged = if last_num + 1 == block.number {
+            last.hash != block.parent_hash
+        } else {
+            self.fetch_block_hash(last_num).await? != last.hash
+        };
+        if !reorged {
+            return Ok(ReconciledBlock::new(block));
+        }
+
+        let orphaned = self
+            .find_orphaned(block.number, last_num, &recent_blocks)
+            .await?;
+
+        let mut events: Vec<ScannedTransaction> = orphaned
+            .iter()
+            .flat_map(|num| recent_blocks[num].transactions.iter().cloned())
+            .map(ScannedTransaction::into_revert)
+            .collect();
+
+        // 重新扫描孤块高度上的规范链区块
+        let mut blocks = Vec::new();
+        for &canonical_num in orphaned.iter().rev() {
+            let canonical_block = self.fetch_block(canonical_num).await?;
+            events.extend(canonical_block.transactions.iter().cloned());
+            blocks.push(canonical_block);
+        }
+
+        events.extend(block.transactions.iter().cloned());
+        blocks.push(block);
+
+        Ok(ReconciledBlock {
+            events,
+            orphaned,
+            blocks,
+        })
+    }
+
+    async fn commit_block(&self, reconciled: &ReconciledBlock) {
+        let mut recent_blocks = self.recent_blocks.lock().await;
+        for num in &reconciled.orphaned {
+            recent_blocks.remove(num);
+        }
+        for block in &reconciled.blocks {
+            recent_blocks.insert(block.number, block.clone());
+        }
+
+        // 只保留最近 max_reorg_depth 个区块高度
+        if let Some(&newest) = recent_blocks.keys().next_back() {
+            let oldest = (newest + 1).saturating_sub(self.max_reorg_depth);
+            *recent_blocks = recent_blocks.split_off(&oldest);
+        }
+    }
+
+    async fn filter_transactions(
+        &self,
+        transactions: Vec<ScannedTransaction>,
+    ) -> Vec<ScannedTransaction> {
+        self.watchlist.filter(transactions)
+    }
+
+    async fn enrich_transactions(&self, transactions: &mut [ScannedTransaction]) {
+        self.tokens.annotate(transactions, self).await;
+    }
+
+    async fn parse_transaction(
+        &self,
+        transaction: &Value,
+        receipt: &Value,
+    ) -> Result<ScannedTransaction, AppError> {
+        // 获取常见的交易字段
+        let tx_hash = transaction
+            .get("hash")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| AppError::ParseError("Missing transaction hash".to_string()))?;
+
+        let block_number_hex = transaction
+            .get("blockNumber")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| AppError::ParseError("Missing block number".to_string()))?;
+
+        // 将block_number从十六进制转换为十进制
+        let block_number = u64::from_str_radix(block_number_hex.trim_start_matches("0x"), 16)
+            .map_err(|_| AppError::ParseError("Failed to parse block number".to_string()))?;
+
+        let block_hash = transaction
+            .get("blockHash")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string());
+
+        let from_address = transaction
+            .get("from")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| AppError::ParseError("Missing from address".to_string()))?;
+
+        let to_address = transaction
+            .get("to")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string()); // 'to' 可能为空（合约创建）
+
+        // 合约创建交易在 receipt 中返回新合约地址
+        let contract_address = receipt
+            .get("contractAddress")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string());
+
+        let gas_used = receipt
+            .get("gasUsed")
+            .and_then(|t| t.as_str())
+            .and_then(|t| u64::from_str_radix(t.trim_start_matches("0x"), 16).ok())
+            .unwrap_or(0); // 从 receipt 获取 gasUsed
+
+        let value_hex = transaction
+            .get("value")
+            .and_then(|t| t.as_str())
+            .unwrap_or("0x0"); // 如果 value 为空，设置为默认的 "0x0"
+
+        // 尝试将 value 从十六进制转换为 BigInt
+        let value =
+            BigInt::from_str_radix(value_hex.trim_start_matches("0x"), 16).map_err(|e| {
+                println!("Failed to parse value_hex: {}", value_hex); // 打印错误的值
+                AppError::ParseError(format!("Failed to parse value: {}", e))
+            })?;
+
+        // 将 BigInt 转换为字符串
+        let value_string = value.to_string();
+
+        // 获取交易状态（确认数由扫描循环根据链头计算）
+        let status_hex = receipt
+            .get("status")
+            .and_then(|t| t.as_str())
+            .unwrap_or("unknown");
+
+        // 将交易状态从十六进制转换为十进制
+        let status = match u64::from_str_radix(status_hex.trim_start_matches("0x"), 16) {
+            Ok(1) => TransactionStatus::Success,
+            Ok(0) => TransactionStatus::Failed,
+            _ => TransactionStatus::Unknown,
+        };
+
+        // 创建一个空的 Vec 作为默认值
+        let empty_logs: Vec<Value> = Vec::new();
+        let logs = receipt
+            .get("logs")
+            .and_then(|t| t.as_array())
+            .unwrap_or(&empty_logs);
+
+        let is_contract = !logs.is_empty(); // 如果有 logs，说明是合约交互
+
+        // 如果是合约交易，解析 logs 中的 token_transfer
+        let mut token_transfers = Vec::new();
+        for (position, log) in logs.iter().enumerate() {
+            let topics: Vec<&str> = log
+                .get("topics")
+                .and_then(|t| t.as_array())
+                .map(|t| t.iter().filter_map(|topic| topic.as_str()).collect())
+                .unwrap_or_default();
+            let data = log.get("data").and_then(|d| d.as_str()).unwrap_or_default();
+            let contract_address = log
+                .get("address")
+                .and_then(|a| a.as_str())
+                .unwrap_or_default()
+                .to_lowercase();
+            let log_index = log
+                .get("logIndex")
+                .and_then(|i| i.as_str())
+                .and_then(|i| u64::from_str_radix(i.trim_start_matches("0x"), 16).ok())
+                .unwrap_or(position as u64);
+
+            match decode_transfer_log(&topics, data) {
+                Ok(Some(transfer)) => token_transfers.push(TokenTransfer {
+                    event_type: TokenEventType::Transfer,
+                    contract_address,
+                    log_index: Some(log_index),
+                    standard: if transfer.token_id.is_some() {
+                        TokenStandard::Erc721
+                    } else {
+                        TokenStandard::Erc20
+                    },
+                    from: format!("0x{}", transfer.from),
+                    to: format!("0x{}", transfer.to),
+                    value: transfer.value.to_string(),
+                    token_id: transfer.token_id.map(|id| id.to_string()),
+                    symbol: None,
+                    decimals: None,
+                    amount: None,
+                }),
+                Ok(None) => {}
+                // 不符合标准的 Transfer 日志只跳过该日志，不影响整笔交易
+                Err(e) => warn!("Skipping malformed Transfer log in {}: {}", tx_hash, e),
+            }
+        }
+
+        // 构建解析后的交易数据
+        Ok(ScannedTransaction {
+            chain: self.chain_name().to_string(),
+            event: TransactionEvent::Transaction,
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            block_hash,
+            from: Some(from_address.to_string()),
+            to: to_address,
+            value: Some(value_string), // 使用字符串形式的 value
+            status,
+            confirmations: 0,
+            contract_address,
+            token_transfers,
+            details: ChainDetails::Evm(EvmDetails {
+                chain_id: self.chain_id,
+                native_symbol: self.native_symbol.clone(),
+                gas_used,
+                is_contract,
+            }),
+        })
+    }
+
+    fn get_start_block(&self) -> u64 {
+        self.start_block
+    }
+
+    /// 通过 eth_chainId 确认每个端点都属于配置的链，避免把其他网络的数据写入同一个断点
+    async fn verify_node(&self) -> Result<(), AppError> {
+        for url in self.endpoints.urls() {
+            // 暂时不可达的端点跳过，之后请求失败时会自动切换
+            let result = match self.request_at(url, "eth_chainId", json!([])).await {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!(
+                        "Could not verify {} node at {}: {}",
+                        self.chain_name, url, e
+                    );
+                    continue;
+                }
+            };
+            let chain_id = result
+                .as_str()
+                .and_then(|id| u64::from_str_radix(id.trim_start_matches("0x"), 16).ok())
+                .ok_or_else(|| AppError::ParseError(format!("Invalid chain id: {}", result)))?;
+
+            if chain_id != self.chain_id {
+                return Err(AppError::ChainMismatch(format!(
+                    "{} node at {} reports chain id {}, expected {}",
+                    self.chain_name, url, chain_id, self.chain_id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanners::watchlist::AddressFormat;
+    use crate::storage::token_metadata::TokenMetadataStore;
+    use crate::utils::test_http;
+    use std::collections::HashSet;
+
+    /// 节点上的规范链：区块号 -> (哈希, 父哈希)，failing 中的区块返回错误
+    #[derive(Default)]
+    struct TestChain {
+        blocks: HashMap<u64, (String, String)>,
+        failing: HashSet<u64>,
+    }
+
+    type SharedChain = Arc<std::sync::Mutex<TestChain>>;
+
+    async fn test_scanner(chain: SharedChain) -> EvmScanner {
+        let url = test_http::serve(move |request| {
+            assert_eq!(
+                request.headers.get("content-type").map(String::as_str),
+                Some("application/json")
+            );
+            let request = request.json();
+            let block_num = u64::from_str_radix(
+                request["params"][0]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("0x"),
+                16,
+            )
+            .unwrap();
+            let chain = chain.lock().unwrap();
+            let body = match chain.blocks.get(&block_num) {
+                Some((hash, parent_hash)) if !chain.failing.contains(&block_num) => json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": { "hash": hash, "parentHash": parent_hash, "transactions": [] }
+                }),
+                _ => json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": -32000, "message": "unavailable" }
+                }),
+            };
+            (200, body.to_string())
+        })
+        .await;
+        scanner_at(&url).await
+    }
+
+    async fn scanner_at(url: &str) -> EvmScanner {
+        let config: ChainConfig = serde_json::from_value(json!({
+            "kind": "evm",
+            "name": "test",
+            "api_url": url,
+            "start_block": 0,
+            "options": {
+                "chain_id": 1,
+                "native_symbol": "ETH",
+                "block_fetch_mode": "transaction_receipts"
+            }
+        }))
+        .unwrap();
+        let options: EvmOptions = config.options().unwrap();
+        let pool = crate::storage::connect_in_memory().await;
+        let tokens = Arc::new(TokenRegistry::new(
+            "test",
+            TokenMetadataStore::new(pool).await.unwrap(),
+        ));
+        let watchlist = Arc::new(Watchlist::new(
+            "test",
+            AddressFormat::Evm,
+            &config.watchlist,
+        ));
+        EvmScanner::new(&config, &options, watchlist, tokens).unwrap()
+    }
+
+    fn transaction(block_number: u64, tx_hash: &str) -> ScannedTransaction {
+        ScannedTransaction {
+            chain: "test".to_string(),
+            event: TransactionEvent::Transaction,
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            block_hash: None,
+            from: None,
+            to: None,
+            value: None,
+            status: TransactionStatus::Success,
+            confirmations: 0,
+            contract_address: None,
+            token_transfers: Vec::new(),
+            details: ChainDetails::Evm(EvmDetails {
+                chain_id: 1,
+                native_symbol: "ETH".to_string(),
+                gas_used: 21000,
+                is_contract: false,
+            }),
+        }
+    }
+
+    fn block(number: u64, hash: &str, parent_hash: &str, txs: &[&str]) -> ScannedBlock {
+        ScannedBlock {
+            number,
+            hash: hash.to_string(),
+            parent_hash: parent_hash.to_string(),
+            transactions: txs.iter().map(|tx| transaction(number, tx)).collect(),
+        }
+    }
+
+    fn set_chain(chain: &SharedChain, blocks: &[(u64, &str, &str)]) {
+        let mut chain = chain.lock().unwrap();
+        for (number, hash, parent_hash) in blocks {
+            chain
+                .blocks
+                .insert(*number, (hash.to_string(), parent_hash.to_string()));
+        }
+    }
+
+    async fn recorded_hash(scanner: &EvmScanner, number: u64) -> Option<String> {
+        let recent_blocks = scanner.recent_blocks.lock().await;
+        recent_blocks.get(&number).map(|block| block.hash.clone())
+    }
+
+    fn balance_calls() -> Vec<(&'static str, Value)> {
+        ["0x1", "0x2", "0x3"]
+            .into_iter()
+            .map(|address| ("eth_getBalance", json!([address, "latest"])))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn batch_responses_are_matched_by_id() {
+        // 节点把批量响应倒序返回，并且丢掉了第二个调用的响应
+        let url = test_http::serve(|request| {
+            let items: Vec<Value> = request
+                .json()
+                .as_array()
+                .unwrap()
+                .iter()
+                .rev()
+                .filter(|call| call["params"][0] != "0x2")
+                .map(|call| {
+                    json!({ "jsonrpc": "2.0", "id": call["id"], "result": call["params"][0] })
+                })
+                .collect();
+            (200, Value::Array(items).to_string())
+        })
+        .await;
+        let scanner = scanner_at(&url).await;
+
+        let results = scanner.make_batch_request(&balance_calls()).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), "0x1");
+        assert!(
+            matches!(&results[1], Err(AppError::ApiError(e)) if e.contains("Missing response"))
+        );
+        assert_eq!(results[2].as_ref().unwrap(), "0x3");
+    }
+
+    #[tokio::test]
+    async fn non_array_batch_response_rejects_the_batch() {
+        let url = test_http::serve(|_| {
+            let error = json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32600, "message": "batch requests are not supported" }
+            });
+            (200, error.to_string())
+        })
+        .await;
+        let scanner = scanner_at(&url).await;
+
+        let result = scanner.make_batch_request(&balance_calls()).await;
+        assert!(
+            matches!(result, Err(AppError::ApiError(e)) if e.contains("Batch request rejected"))
+        );
+    }
+
+    #[tokio::test]
+    async fn reorg_reverts_orphaned_blocks_only_after_commit() {
+        let chain = SharedChain::default();
+        let scanner = test_scanner(chain.clone()).await;
+        scanner
+            .commit_block(&ReconciledBlock::new(block(10, "a10", "a9", &[])))
+            .await;
+        scanner
+            .commit_block(&ReconciledBlock::new(block(11, "a11", "a10", &["0xold"])))
+            .await;
+        set_chain(
+            &chain,
+            &[(10, "a10", "a9"), (11, "b11", "a10"), (12, "b12", "b11")],
+        );
+
+        let scanned = scanner.scan_block(12).await.unwrap();
+        let reconciled = scanner.reconcile_block(scanned).await.unwrap();
+
+        assert_eq!(reconciled.orphaned, vec![11]);
+        assert_eq!(reconciled.events.len(), 1);
+        assert_eq!(reconciled.events[0].event, TransactionEvent::Revert);
+        assert_eq!(reconciled.events[0].tx_hash, "0xold");
+        let blocks: Vec<(u64, &str)> = reconciled
+            .blocks
+            .iter()
+            .map(|block| (block.number, block.hash.as_str()))
+            .collect();
+        assert_eq!(blocks, vec![(11, "b11"), (12, "b12")]);
+
+        // 提交之前记录不变，重试会再次得到同样的结果
+        assert_eq!(recorded_hash(&scanner, 11).await.as_deref(), Some("a11"));
+        scanner.commit_block(&reconciled).await;
+        assert_eq!(recorded_hash(&scanner, 11).await.as_deref(), Some("b11"));
+        assert_eq!(recorded_hash(&scanner, 12).await.as_deref(), Some("b12"));
+    }
+
+    #[tokio::test]
+    async fn failed_fetch_during_reorg_keeps_orphaned_blocks() {
+        let chain = SharedChain::default();
+        let scanner = test_scanner(chain.clone()).await;
+        scanner
+            .commit_block(&ReconciledBlock::new(block(10, "a10", "a9", &[])))
+            .await;
+        scanner
+            .commit_block(&ReconciledBlock::new(block(11, "a11", "a10", &["0xold"])))
+            .await;
+        set_chain(
+            &chain,
+            &[(10, "a10", "a9"), (11, "b11", "a10"), (12, "b12", "b11")],
+        );
+
+        let scanned = scanner.scan_block(12).await.unwrap();
+        chain.lock().unwrap().failing.insert(11);
+        assert!(scanner.reconcile_block(scanned.clone()).await.is_err());
+        assert_eq!(recorded_hash(&scanner, 11).await.as_deref(), Some("a11"));
+
+        chain.lock().unwrap().failing.clear();
+        let reconciled = scanner.reconcile_block(scanned).await.unwrap();
+        assert_eq!(reconciled.orphaned, vec![11]);
+        assert_eq!(reconciled.events[0].tx_hash, "0xold");
+    }
+
+    #[tokio::test]
+    async fn missing_parent_checks_last_committed_block() {
+        let chain = SharedChain::default();
+        let scanner = test_scanner(chain.clone()).await;
+        scanner
+            .commit_block(&ReconciledBlock::new(block(10, "a10", "a9", &["0xold"])))
+            .await;
+
+        // 区块 11 没有提交（例如进了死信列表），区块 10 仍在规范链上
+        set_chain(&chain, &[(10, "a10", "a9"), (12, "a12", "a11")]);
+        let reconciled = scanner
+            .reconcile_block(block(12, "a12", "a11", &["0xnew"]))
+            .await
+            .unwrap();
+        assert!(reconciled.orphaned.is_empty());
+        assert_eq!(reconciled.events.len(), 1);
+        assert_eq!(reconciled.events[0].tx_hash, "0xnew");
+
+        // 区块 10 已被替换时不能因为缺少父区块而跳过检查
+        set_chain(&chain, &[(10, "b10", "a9"), (12, "b12", "b11")]);
+        let reconciled = scanner
+            .reconcile_block(block(12, "b12", "b11", &[]))
+            .await
+            .unwrap();
+        assert_eq!(reconciled.orphaned, vec![10]);
+        assert_eq!(reconciled.events[0].event, TransactionEvent::Revert);
+        assert_eq!(reconciled.events[0].tx_hash, "0xold");
+    }
+
+    #[tokio::test]
+    async fn commit_keeps_only_max_reorg_depth_heights() {
+        let scanner = test_scanner(SharedChain::default()).await;
+        for number in 0..100 {
+            scanner
+                .commit_block(&ReconciledBlock::new(block(number, "h", "h", &[])))
+                .await;
+        }
+        let recent_blocks = scanner.recent_blocks.lock().await;
+        assert_eq!(recent_blocks.len() as u64, scanner.max_reorg_depth);
+        assert_eq!(
+            recent_blocks.keys().next(),
+            Some(&(100 - scanner.max_reorg_depth))
+        );
+    }
+}
@@ -1,21 +1,47 @@
-pub mod bsc;
+pub mod evm;
+pub mod registry;
 pub mod tron;
+pub mod types;
+pub mod watchlist;
 
 use crate::utils::error::AppError;
 use async_trait::async_trait;
 use serde_json::Value;
+use types::{ReconciledBlock, ScannedBlock, ScannedTransaction};
 
 #[async_trait]
 pub trait BlockchainScanner: Send + Sync {
     fn chain_name(&self) -> &str;
     async fn get_latest_block(&self) -> Result<u64, AppError>;
-    async fn scan_block(&self, block_num: u64) -> Result<Vec<Value>, AppError>;
-    async fn filter_transactions(&self, transactions: Vec<Value>) -> Vec<Value>;
+    /// 拉取并解析区块，不修改扫描状态，可以并发调用
+    async fn scan_block(&self, block_num: u64) -> Result<ScannedBlock, AppError>;
+
+    /// 按区块顺序对 scan_block 的结果检查链重组，返回需要发出的事件。
+    /// 只读取已提交的状态，记录由 commit_block 完成；默认不检查
+    async fn reconcile_block(&self, block: ScannedBlock) -> Result<ReconciledBlock, AppError> {
+        Ok(ReconciledBlock::new(block))
+    }
+
+    /// 记录 reconcile_block 的结果，供之后的区块检查链重组；默认不记录
+    async fn commit_block(&self, _block: &ReconciledBlock) {}
+
+    async fn filter_transactions(
+        &self,
+        transactions: Vec<ScannedTransaction>,
+    ) -> Vec<ScannedTransaction>;
     fn get_start_block(&self) -> u64;
 
+    /// 启动时检查节点是否属于配置的链，默认不检查
+    async fn verify_node(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// 过滤之后补充代币元数据等需要额外请求的信息，默认不做处理
+    async fn enrich_transactions(&self, _transactions: &mut [ScannedTransaction]) {}
+
     async fn parse_transaction(
         &self,
         transaction: &Value,
         receipt: &Value,
-    ) -> Result<Value, AppError>;
+    ) -> Result<ScannedTransaction, AppError>;
 }
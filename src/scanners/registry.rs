@@ -0,0 +1,83 @@
+use crate::scanners::evm::EvmScanner;
+use crate::scanners::tron::TronScanner;
+use crate::scanners::watchlist::{AddressFormat, Watchlist};
+use crate::scanners::BlockchainScanner;
+use crate::tokens::TokenRegistry;
+use crate::utils::config::{ChainConfig, EvmOptions, TronOptions};
+use crate::utils::error::AppError;
+use config::ConfigError;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 根据链配置、监控地址列表和代币注册表创建扫描器
+pub type ScannerConstructor = fn(
+    &ChainConfig,
+    Arc<Watchlist>,
+    Arc<TokenRegistry>,
+) -> Result<Box<dyn BlockchainScanner>, AppError>;
+
+/// 一种扫描器：地址格式决定监控地址如何归一化，构造函数负责解析该类型的 options
+pub struct ScannerKind {
+    pub address_format: AddressFormat,
+    pub constructor: ScannerConstructor,
+}
+
+/// 扫描器类型注册表，把配置中的 kind 映射到对应的构造函数
+pub struct ScannerRegistry {
+    kinds: BTreeMap<&'static str, ScannerKind>,
+}
+
+impl ScannerRegistry {
+    pub fn new() -> Self {
+        ScannerRegistry {
+            kinds: BTreeMap::new(),
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        kind: &'static str,
+        address_format: AddressFormat,
+        constructor: ScannerConstructor,
+    ) {
+        self.kinds.insert(
+            kind,
+            ScannerKind {
+                address_format,
+                constructor,
+            },
+        );
+    }
+
+    pub fn get(&self, kind: &str) -> Result<&ScannerKind, AppError> {
+        self.kinds.get(kind).ok_or_else(|| {
+            let known: Vec<&str> = self.kinds.keys().copied().collect();
+            AppError::ConfigError(ConfigError::Message(format!(
+                "Unknown scanner kind {}, expected one of: {}",
+                kind,
+                known.join(", ")
+            )))
+        })
+    }
+}
+
+impl Default for ScannerRegistry {
+    /// 包含所有内置扫描器类型
+    fn default() -> Self {
+        let mut registry = ScannerRegistry::new();
+        registry.register("tron", AddressFormat::Tron, |config, watchlist, tokens| {
+            let options: TronOptions = config.options()?;
+            options.validate(&config.name)?;
+            Ok(Box::new(TronScanner::new(
+                config, &options, watchlist, tokens,
+            )?))
+        });
+        registry.register("evm", AddressFormat::Evm, |config, watchlist, tokens| {
+            let options: EvmOptions = config.options()?;
+            Ok(Box::new(EvmScanner::new(
+                config, &options, watchlist, tokens,
+            )?))
+        });
+        registry
+    }
+}
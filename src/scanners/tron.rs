@@ -1,217 +1,1238 @@
+use crate::rpc::api_keys::ApiKeyRing;
+use crate::rpc::endpoints::EndpointPool;
+use crate::rpc::rate_limit::TokenBucket;
+use crate::rpc::{build_client, post_json, send_json};
+use crate::scanners::types::{
+    ChainDetails, ScannedBlock, ScannedTransaction, TokenCall, TokenEventType, TokenStandard,
+    TokenTransfer, TransactionEvent, TransactionStatus, TronContract, TronDetails, TronResources,
+    TronVote,
+};
+use crate::scanners::watchlist::Watchlist;
 use crate::scanners::BlockchainScanner;
+use crate::tokens::{format_units, TokenMetadataSource, TokenRegistry, ViewMethod};
+use crate::utils::abi::{
+    decode_token_call, decode_transfer_log, DecodedTokenCall, TokenCallMethod,
+};
+use crate::utils::config::{ChainConfig, TronOptions};
 use crate::utils::error::AppError;
-use crate::utils::tron::hex_to_tron_address;
+use crate::utils::tron::{decode_asset_name, hex_to_tron_address, tron_address_or_raw};
 use async_trait::async_trait;
-use num_bigint::BigInt;
-use num_traits::Num;
 use reqwest::Client;
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// TronGrid 鉴权请求头
+const API_KEY_HEADER: &str = "TRON-PRO-API-KEY";
+
+/// 配置了 key 但未指定 requests_per_second 时每个 key 的默认速率
+const DEFAULT_KEY_REQUESTS_PER_SECOND: f64 = 15.0;
+
+/// 服务端没有说明暂停时长时 key 的默认暂停时间
+const DEFAULT_KEY_SUSPENSION: Duration = Duration::from_secs(30);
 
 pub struct TronScanner {
-    api_url: String,
+    chain_name: String,
+    endpoints: EndpointPool,
     client: Client,
+    api_keys: ApiKeyRing,
+    /// 没有配置 key 时对匿名请求的限流
+    anonymous_limiter: Option<TokenBucket>,
     start_block: u64,
+    watchlist: Arc<Watchlist>,
+    tokens: Arc<TokenRegistry>,
 }
 
 impl TronScanner {
-    pub fn new(api_url: &str, start_block: u64) -> Result<Self, AppError> {
-        let api_url = api_url.trim_end_matches('/').to_string();
+    pub fn new(
+        config: &ChainConfig,
+        options: &TronOptions,
+        watchlist: Arc<Watchlist>,
+        tokens: Arc<TokenRegistry>,
+    ) -> Result<Self, AppError> {
+        let requests_per_second = options
+            .requests_per_second
+            .unwrap_or(DEFAULT_KEY_REQUESTS_PER_SECOND);
+        let burst = options.burst.unwrap_or(requests_per_second.ceil() as u32);
+
+        let anonymous_limiter = match options.requests_per_second {
+            Some(rate) if options.api_keys.is_empty() => Some(TokenBucket::new(rate, burst)),
+            _ => None,
+        };
+
         Ok(TronScanner {
-            api_url,
-            client: Client::new(),
-            start_block,
+            chain_name: config.name.clone(),
+            endpoints: EndpointPool::new(&config.name, &config.all_endpoints(), &config.rpc)?,
+            client: build_client(&config.name, &config.http)?,
+            api_keys: ApiKeyRing::new(&options.api_keys, requests_per_second, burst),
+            anonymous_limiter,
+            start_block: config.start_block,
+            watchlist,
+            tokens,
         })
     }
 
     async fn make_http_request(&self, method: &str, params: Value) -> Result<Value, AppError> {
-        let url = format!("{}/{}", self.api_url, method);
-        let response = self
-            .client
-            .post(&url)
-            .json(&params)
-            .send()
+        let params = &params;
+        self.endpoints
+            .call(|url| async move { self.request_at(&url, method, params).await })
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+    }
 
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+    /// 向指定端点发送请求，不做故障切换。
+    ///
+    /// 配置了 API key 时轮流使用各个 key，某个 key 被限流后暂停它并换下一个 key 重发，
+    /// 所有 key 都被暂停时返回 QuotaExceeded。
+    async fn request_at(&self, url: &str, method: &str, params: &Value) -> Result<Value, AppError> {
+        let url = format!("{}/{}", url, method);
+
+        if self.api_keys.is_empty() {
+            if let Some(limiter) = &self.anonymous_limiter {
+                limiter.acquire().await;
+            }
+            return post_json(&self.client, &url, params).await;
+        }
+
+        loop {
+            let index = self.api_keys.acquire().await?;
+            let request = self
+                .client
+                .post(&url)
+                .header(API_KEY_HEADER, self.api_keys.key(index))
+                .json(params);
+
+            match send_json(request).await.map_err(classify_key_error) {
+                Err(AppError::ApiKeyRateLimited(suspend_for, reason)) => {
+                    self.api_keys.suspend(index, suspend_for, &reason);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// TronGrid 对超出频率或额度的 key 返回 429，或返回 403 和
+/// "The key exceeds the frequency limit(15), and the query server is suspended for 30s"
+fn classify_key_error(error: AppError) -> AppError {
+    match error {
+        AppError::RateLimited(retry_after, body) => {
+            AppError::ApiKeyRateLimited(retry_after.unwrap_or(DEFAULT_KEY_SUSPENSION), body)
+        }
+        AppError::HttpError(403, body) if body.contains("exceeds") => {
+            let suspend_for = body
+                .split("suspended for ")
+                .nth(1)
+                .and_then(|rest| {
+                    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+                    digits.parse().ok()
+                })
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_KEY_SUSPENSION);
+            AppError::ApiKeyRateLimited(suspend_for, body)
+        }
+        error => error,
+    }
+}
 
-        if !status.is_success() {
+/// TRX 的精度，1 TRX = 10^6 sun
+const TRX_DECIMALS: u8 = 6;
+
+/// 从交易信息中整理资源消耗。节点省略值为 0 的字段，使用免费带宽的转账只有 net_usage；
+/// fee 是调用者支付的总费用，包含 net_fee、energy_fee 以及激活账户等其他费用
+fn parse_resources(info: &Value) -> TronResources {
+    let receipt = &info["receipt"];
+    let uint = |field: &str| receipt[field].as_u64().unwrap_or(0);
+
+    let bandwidth_fee_sun = uint("net_fee");
+    let energy_fee_sun = uint("energy_fee");
+    let energy_from_stake = uint("energy_usage");
+    let energy_from_origin = uint("origin_energy_usage");
+    let energy_used = uint("energy_usage_total");
+    let total_fee_sun = info["fee"]
+        .as_u64()
+        .unwrap_or(bandwidth_fee_sun.saturating_add(energy_fee_sun));
+
+    TronResources {
+        bandwidth_used: uint("net_usage"),
+        bandwidth_fee_sun,
+        energy_used,
+        energy_from_stake,
+        energy_from_origin,
+        energy_burned: energy_used
+            .saturating_sub(energy_from_stake.saturating_add(energy_from_origin)),
+        energy_fee_sun,
+        energy_penalty: uint("energy_penalty_total"),
+        other_fee_sun: total_fee_sun
+            .saturating_sub(bandwidth_fee_sun.saturating_add(energy_fee_sun)),
+        total_fee_sun,
+        total_fee_trx: format_units(&total_fee_sun.to_string(), TRX_DECIMALS),
+    }
+}
+
+/// 把调用数据中的 TRC-20 方法转换为输出结构，并与交易的 Transfer 日志核对；
+/// 成功的 transfer / transferFrom 应有一条地址和数量都相同的日志
+fn build_token_call(
+    call: DecodedTokenCall,
+    contract_address: &str,
+    caller: &str,
+    receipt_result: &Option<String>,
+    log_transfers: Option<&[TokenTransfer]>,
+) -> Result<TokenCall, AppError> {
+    let from = match &call.from {
+        Some(from) => hex_to_tron_address(from)?,
+        None => caller.to_string(),
+    };
+    let to = hex_to_tron_address(&call.to)?;
+    let value = call.value.to_string();
+
+    let succeeded = matches!(receipt_result.as_deref(), None | Some("SUCCESS"));
+    let log_mismatch = match (call.method, log_transfers) {
+        (TokenCallMethod::Approve, _) | (_, None) => false,
+        (_, Some(transfers)) => {
+            let logged = transfers.iter().any(|transfer| {
+                transfer.event_type == TokenEventType::Transfer
+                    && transfer.contract_address == contract_address
+                    && transfer.from == from
+                    && transfer.to == to
+                    && transfer.value == value
+            });
+            succeeded != logged
+        }
+    };
+
+    Ok(TokenCall {
+        method: call.method,
+        contract_address: contract_address.to_string(),
+        from,
+        to,
+        value,
+        result: receipt_result.clone(),
+        log_mismatch,
+    })
+}
+
+/// 解码 raw_data.contract[0] 的参数，TRON 交易只包含一个合约
+fn decode_contract(contract: &Value) -> TronContract {
+    let parameter = &contract["parameter"]["value"];
+    let address = |field: &str| {
+        parameter[field]
+            .as_str()
+            .map(tron_address_or_raw)
+            .unwrap_or_default()
+    };
+    let uint = |field: &str| parameter[field].as_u64().unwrap_or(0);
+    // 资源为 BANDWIDTH 时节点省略该字段（protobuf 默认值）
+    let resource = || {
+        parameter["resource"]
+            .as_str()
+            .unwrap_or("BANDWIDTH")
+            .to_string()
+    };
+
+    match contract["type"].as_str().unwrap_or_default() {
+        "TransferContract" => TronContract::Transfer {
+            amount: uint("amount"),
+        },
+        "TransferAssetContract" => TronContract::TransferAsset {
+            asset_id: decode_asset_name(parameter["asset_name"].as_str().unwrap_or_default()),
+            amount: uint("amount"),
+        },
+        "TriggerSmartContract" => TronContract::TriggerSmartContract {
+            contract_address: address("contract_address"),
+            call_value: uint("call_value"),
+            call_token_value: uint("call_token_value"),
+            token_id: parameter["token_id"]
+                .as_u64()
+                .filter(|id| *id > 0)
+                .map(|id| id.to_string()),
+            data: parameter["data"]
+                .as_str()
+                .filter(|data| !data.is_empty())
+                .map(|data| data.to_string()),
+        },
+        "FreezeBalanceV2Contract" => TronContract::FreezeBalanceV2 {
+            resource: resource(),
+            amount: uint("frozen_balance"),
+        },
+        "UnfreezeBalanceV2Contract" => TronContract::UnfreezeBalanceV2 {
+            resource: resource(),
+            amount: uint("unfreeze_balance"),
+        },
+        "DelegateResourceContract" => TronContract::DelegateResource {
+            resource: resource(),
+            amount: uint("balance"),
+            receiver: address("receiver_address"),
+            lock: parameter["lock"].as_bool().unwrap_or(false),
+            lock_period: uint("lock_period"),
+        },
+        "UnDelegateResourceContract" => TronContract::UnDelegateResource {
+            resource: resource(),
+            amount: uint("balance"),
+            receiver: address("receiver_address"),
+        },
+        "VoteWitnessContract" => TronContract::VoteWitness {
+            votes: parameter["votes"]
+                .as_array()
+                .map(|votes| {
+                    votes
+                        .iter()
+                        .map(|vote| TronVote {
+                            witness: vote["vote_address"]
+                                .as_str()
+                                .map(tron_address_or_raw)
+                                .unwrap_or_default(),
+                            count: vote["vote_count"].as_u64().unwrap_or(0),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        },
+        "AccountCreateContract" => TronContract::AccountCreate {
+            account_address: address("account_address"),
+        },
+        "CreateSmartContract" => {
+            let new_contract = &parameter["new_contract"];
+            TronContract::CreateSmartContract {
+                name: new_contract["name"].as_str().map(|name| name.to_string()),
+                call_value: new_contract["call_value"].as_u64().unwrap_or(0),
+                consume_user_resource_percent: new_contract["consume_user_resource_percent"]
+                    .as_u64(),
+                origin_energy_limit: new_contract["origin_energy_limit"].as_u64(),
+            }
+        }
+        _ => TronContract::Other,
+    }
+}
+
+/// 常量调用使用的调用者地址（全零地址）
+const CONSTANT_CALL_OWNER: &str = "T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb";
+
+#[async_trait]
+impl TokenMetadataSource for TronScanner {
+    async fn call_view(
+        &self,
+        contract_address: &str,
+        method: &ViewMethod,
+    ) -> Result<String, AppError> {
+        let params = json!({
+            "owner_address": CONSTANT_CALL_OWNER,
+            "contract_address": contract_address,
+            "function_selector": method.signature,
+            "visible": true
+        });
+        let result = self
+            .make_http_request("wallet/triggerconstantcontract", params)
+            .await?;
+
+        // 方法被回滚时交易结果为 FAILED，合约不存在时返回 CONTRACT_VALIDATE_ERROR；
+        // 其他失败可能是临时的，按普通错误返回
+        let reverted = result["transaction"]["ret"][0]["ret"].as_str() == Some("FAILED")
+            || result["result"]["code"].as_str() == Some("CONTRACT_VALIDATE_ERROR");
+        if reverted {
+            return Err(AppError::ContractReverted(format!(
+                "triggerconstantcontract {} on {}: {}",
+                method.signature, contract_address, result["result"]
+            )));
+        }
+        if !result["result"]["result"].as_bool().unwrap_or(false) {
             return Err(AppError::ApiError(format!(
-                "HTTP error: {}, body: {}",
-                status, body
+                "triggerconstantcontract {} on {} failed: {}",
+                method.signature, contract_address, result["result"]
             )));
         }
 
-        serde_json::from_str(&body).map_err(|e| AppError::JsonParseError(e.to_string()))
+        result["constant_result"][0]
+            .as_str()
+            .map(|r| r.to_string())
+            .ok_or_else(|| AppError::ParseError("Missing constant_result".to_string()))
     }
 }
 
 #[async_trait]
 impl BlockchainScanner for TronScanner {
     fn chain_name(&self) -> &str {
-        "TRON"
+        &self.chain_name
     }
 
     async fn get_latest_block(&self) -> Result<u64, AppError> {
-        let result = self
-            .make_http_request("wallet/getnowblock", json!({}))
-            .await?;
-        let block_number = result["block_header"]["raw_data"]["number"]
-            .as_u64()
-            .ok_or_else(|| AppError::ParseError("Failed to parse block number".to_string()))?;
-
-        Ok(block_number)
+        self.endpoints
+            .latest_block(|url| async move {
+                let result = self
+                    .request_at(&url, "wallet/getnowblock", &json!({}))
+                    .await?;
+                result["block_header"]["raw_data"]["number"]
+                    .as_u64()
+                    .ok_or_else(|| AppError::ParseError("Failed to parse block number".to_string()))
+            })
+            .await
     }
 
-    async fn scan_block(&self, block_num: u64) -> Result<Vec<Value>, AppError> {
+    async fn scan_block(&self, block_num: u64) -> Result<ScannedBlock, AppError> {
         let params = json!({
             "num": block_num
         });
 
-        let result = self
-            .make_http_request("wallet/gettransactioninfobyblocknum", params)
-            .await?;
+        // 区块中有交易原文，交易信息中有回执和日志，两者按交易 id 合并
+        let (block, infos) = tokio::try_join!(
+            self.make_http_request("wallet/getblockbynum", params.clone()),
+            self.make_http_request("wallet/gettransactioninfobyblocknum", params),
+        )?;
+
+        let block_id = block["blockID"]
+            .as_str()
+            .ok_or_else(|| AppError::ParseError(format!("Block {} not found", block_num)))?;
+
+        let parent_hash = block["block_header"]["raw_data"]["parentHash"]
+            .as_str()
+            .unwrap_or_default();
 
-        let transactions = result.as_array().ok_or_else(|| {
-            AppError::ParseError(format!(
-                "Failed to parse transactions for block {}",
-                block_num
-            ))
-        })?;
+        // 节点为每笔交易都写入交易信息；HTTP 200 的错误对象或落后节点返回的空列表
+        // 说明数据还没准备好，整个区块失败后重试，不能当作没有日志和手续费的交易
+        let infos: HashMap<&str, &Value> = infos
+            .as_array()
+            .ok_or_else(|| {
+                AppError::ApiError(format!(
+                    "Unexpected transaction info response for block {}: {}",
+                    block_num, infos
+                ))
+            })?
+            .iter()
+            .filter_map(|info| info["id"].as_str().map(|id| (id, info)))
+            .collect();
+
+        // 没有交易的区块不含 transactions 字段
+        let empty_transactions: Vec<Value> = Vec::new();
+        let transactions = block["transactions"]
+            .as_array()
+            .unwrap_or(&empty_transactions);
 
         let mut parsed_transactions = Vec::new();
-        for transaction in transactions.iter() {
-            match self.parse_transaction(transaction, &Value::Null).await {
-                Ok(parsed_tx) => parsed_transactions.push(parsed_tx),
-                Err(e) => println!("Error parsing transaction: {:?}", e),
+        for transaction in transactions {
+            let tx_id = transaction["txID"].as_str().unwrap_or_default();
+            let info = infos.get(tx_id).copied().ok_or_else(|| {
+                AppError::ApiError(format!(
+                    "Transaction info for {} in block {} is not available yet",
+                    tx_id, block_num
+                ))
+            })?;
+            match self.parse_transaction(transaction, info).await {
+                Ok(mut parsed_tx) => {
+                    parsed_tx.block_number = block_num;
+                    parsed_tx.block_hash = Some(block_id.to_string());
+                    parsed_transactions.push(parsed_tx);
+                }
+                Err(e) => warn!(
+                    "Skipping unparsable {} transaction {}: {}",
+                    self.chain_name, tx_id, e
+                ),
             }
         }
 
-        Ok(parsed_transactions)
+        Ok(ScannedBlock {
+            number: block_num,
+            hash: block_id.to_string(),
+            parent_hash: parent_hash.to_string(),
+            transactions: parsed_transactions,
+        })
+    }
+
+    async fn filter_transactions(
+        &self,
+        transactions: Vec<ScannedTransaction>,
+    ) -> Vec<ScannedTransaction> {
+        self.watchlist.filter(transactions)
     }
 
-    async fn filter_transactions(&self, transactions: Vec<Value>) -> Vec<Value> {
-        transactions
+    async fn enrich_transactions(&self, transactions: &mut [ScannedTransaction]) {
+        self.tokens.annotate(transactions, self).await;
     }
 
+    /// transaction 是 getblockbynum 中的交易原文，receipt 是对应的交易信息；
+    /// scan_block 保证交易信息存在，单独调用时可以传入 Null
     async fn parse_transaction(
         &self,
         transaction: &Value,
-        _receipt: &Value,
-    ) -> Result<Value, AppError> {
-        println!("Raw transaction: {:?}", transaction);
-
-        let tx_id = transaction["id"]
+        receipt: &Value,
+    ) -> Result<ScannedTransaction, AppError> {
+        let tx_id = transaction["txID"]
             .as_str()
             .ok_or_else(|| AppError::ParseError("Missing transaction id".to_string()))?;
 
-        let block_number = transaction["blockNumber"]
-            .as_u64()
-            .ok_or_else(|| AppError::ParseError("Invalid block number".to_string()))?;
-
-        let fee = transaction["receipt"]
-            .get("net_fee")
-            .or_else(|| transaction.get("fee"))
-            .and_then(|f| f.as_u64())
-            .unwrap_or(0);
-
-        let energy_usage_total = transaction
-            .get("receipt")
-            .and_then(|r| r.get("energy_usage_total"))
-            .and_then(|e| e.as_u64())
-            .unwrap_or(0);
-
-        let status = transaction
-            .get("receipt")
-            .and_then(|r| r.get("result"))
-            .and_then(|s| s.as_str())
-            .unwrap_or("success");
-
-        let contract_address = transaction
-            .get("contract_address")
-            .and_then(|a| a.as_str())
-            .map(|s| hex_to_tron_address(s).unwrap_or_else(|_| s.to_string()))
-            .unwrap_or_default();
+        // scan_block 会用请求的区块号覆盖
+        let block_number = receipt["blockNumber"].as_u64().unwrap_or_default();
 
-        let mut parsed_tx = json!({
-            "tx_hash": tx_id,
-            "block_number": block_number,
-            "fee": fee,
-            "energy_usage_total": energy_usage_total,
-            "status": status,
-            "contract_address": contract_address,
+        let resources = parse_resources(receipt);
+
+        // 优先使用回执结果，没有时使用区块中交易的 contractRet；两者都没有的普通转账视为成功
+        let receipt_result = receipt["receipt"]["result"]
+            .as_str()
+            .or_else(|| transaction["ret"][0]["contractRet"].as_str())
+            .map(|s| s.to_string());
+
+        let status = match receipt_result.as_deref() {
+            None | Some("SUCCESS") => TransactionStatus::Success,
+            Some(_) => TransactionStatus::Failed,
+        };
+
+        let contract_address = receipt["contract_address"]
+            .as_str()
+            .map(tron_address_or_raw);
+
+        let contract = &transaction["raw_data"]["contract"][0];
+        let contract_type = contract["type"].as_str().unwrap_or("Unknown").to_string();
+        let decoded = decode_contract(contract);
+
+        let parameter = &contract["parameter"]["value"];
+        let from = parameter["owner_address"].as_str().map(tron_address_or_raw);
+        let to_address = parameter["to_address"].as_str().map(tron_address_or_raw);
+
+        let (to, value) = match &decoded {
+            TronContract::Transfer { amount } => (to_address, Some(amount.to_string())),
+            TronContract::TransferAsset { .. } => (to_address, None),
+            TronContract::TriggerSmartContract {
+                contract_address,
+                call_value,
+                ..
+            } => (Some(contract_address.clone()), Some(call_value.to_string())),
+            TronContract::CreateSmartContract { call_value, .. } => {
+                (None, Some(call_value.to_string()))
+            }
+            TronContract::DelegateResource { receiver, .. }
+            | TronContract::UnDelegateResource { receiver, .. } => (Some(receiver.clone()), None),
+            TronContract::AccountCreate { account_address } => {
+                (Some(account_address.clone()), None)
+            }
+            _ => (None, None),
+        };
+
+        // 没有交易信息时从调用参数中取被调用的合约
+        let contract_address = contract_address.or_else(|| match &decoded {
+            TronContract::TriggerSmartContract {
+                contract_address, ..
+            } => Some(contract_address.clone()),
+            _ => None,
         });
 
-        if let Some(contract) = transaction
-            .get("raw_data")
-            .and_then(|rd| rd.get("contract"))
-            .and_then(|c| c.get(0))
+        let mut token_transfers = Vec::new();
+        if let (TronContract::TransferAsset { asset_id, amount }, Some(from), Some(to)) =
+            (&decoded, &from, &to)
         {
-            if let Some(parameter) = contract.get("parameter").and_then(|p| p.get("value")) {
-                if let Some(from) = parameter.get("owner_address").and_then(|a| a.as_str()) {
-                    parsed_tx["from"] = json!(hex_to_tron_address(&format!("41{}", &from))
-                        .unwrap_or_else(|_| from.to_string()));
-                }
-                if let Some(to) = parameter.get("to_address").and_then(|a| a.as_str()) {
-                    parsed_tx["to"] = json!(hex_to_tron_address(&format!("41{}", &to))
-                        .unwrap_or_else(|_| to.to_string()));
-                }
-                if let Some(value) = parameter.get("amount").and_then(|a| a.as_u64()) {
-                    parsed_tx["value"] = json!(value);
+            token_transfers.push(TokenTransfer {
+                event_type: TokenEventType::AssetTransfer,
+                standard: TokenStandard::Trc10,
+                contract_address: asset_id.clone(),
+                log_index: None,
+                from: from.clone(),
+                to: to.clone(),
+                value: amount.to_string(),
+                token_id: None,
+                symbol: None,
+                decimals: None,
+                amount: None,
+            });
+        }
+
+        if let Some(logs) = receipt.get("log").and_then(|l| l.as_array()) {
+            for (log_index, log) in logs.iter().enumerate() {
+                let topics: Vec<&str> = log
+                    .get("topics")
+                    .and_then(|t| t.as_array())
+                    .map(|t| t.iter().filter_map(|topic| topic.as_str()).collect())
+                    .unwrap_or_default();
+                let data = log.get("data").and_then(|d| d.as_str()).unwrap_or_default();
+                // 日志中的合约地址不带 41 前缀
+                let contract_address = log
+                    .get("address")
+                    .and_then(|a| a.as_str())
+                    .map(tron_address_or_raw)
+                    .unwrap_or_default();
+
+                match decode_transfer_log(&topics, data) {
+                    Ok(Some(transfer)) => token_transfers.push(TokenTransfer {
+                        event_type: TokenEventType::Transfer,
+                        contract_address,
+                        log_index: Some(log_index as u64),
+                        standard: if transfer.token_id.is_some() {
+                            TokenStandard::Trc721
+                        } else {
+                            TokenStandard::Trc20
+                        },
+                        from: hex_to_tron_address(&transfer.from)?,
+                        to: hex_to_tron_address(&transfer.to)?,
+                        value: transfer.value.to_string(),
+                        token_id: transfer.token_id.map(|id| id.to_string()),
+                        symbol: None,
+                        decimals: None,
+                        amount: None,
+                    }),
+                    Ok(None) => {}
+                    Err(e) => warn!("Skipping malformed Transfer log in {}: {}", tx_id, e),
                 }
             }
         }
 
-        if let Some(logs) = transaction.get("log").and_then(|l| l.as_array()) {
-            let mut token_transfers = Vec::new();
-            for log in logs {
-                if let Some(topics) = log.get("topics").and_then(|t| t.as_array()) {
-                    if topics.len() >= 3
-                        && topics[0].as_str()
-                            == Some(
-                                "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
-                            )
-                    {
-                        let from = topics
-                            .get(1)
-                            .and_then(|t| t.as_str())
-                            .and_then(|s| hex_to_tron_address(&format!("41{}", &s[24..])).ok())
-                            .unwrap_or_else(|| "Unknown".to_string());
-                        let to = topics
-                            .get(2)
-                            .and_then(|t| t.as_str())
-                            .and_then(|s| hex_to_tron_address(&format!("41{}", &s[24..])).ok())
-                            .unwrap_or_else(|| "Unknown".to_string());
-                        let value = log
-                            .get("data")
-                            .and_then(|d| d.as_str())
-                            .map(|s| {
-                                BigInt::from_str_radix(s.trim_start_matches("0x"), 16)
-                                    .unwrap_or(BigInt::from(0))
-                            })
-                            .unwrap_or(BigInt::from(0));
-
-                        token_transfers.push(json!({
-                            "from": from,
-                            "to": to,
-                            "value": value.to_string()
-                        }));
+        let token_call = match &decoded {
+            TronContract::TriggerSmartContract {
+                contract_address,
+                data: Some(data),
+                ..
+            } => {
+                // 地址无法转换时与调用数据格式错误一样只跳过 token_call，不丢弃整笔交易
+                let token_call = decode_token_call(data).and_then(|call| {
+                    call.map(|call| {
+                        build_token_call(
+                            call,
+                            contract_address,
+                            from.as_deref().unwrap_or_default(),
+                            &receipt_result,
+                            // 没有交易信息时无法判断日志是否一致
+                            (!receipt.is_null()).then_some(token_transfers.as_slice()),
+                        )
+                    })
+                    .transpose()
+                });
+                match token_call {
+                    Ok(Some(token_call)) => {
+                        if token_call.log_mismatch {
+                            warn!(
+                                "TRC-20 call in {} does not match its Transfer logs: {:?}",
+                                tx_id, token_call
+                            );
+                        }
+                        Some(token_call)
+                    }
+                    Ok(None) => None,
+                    Err(e) => {
+                        warn!("Skipping malformed TRC-20 call data in {}: {}", tx_id, e);
+                        None
                     }
                 }
             }
-            if !token_transfers.is_empty() {
-                parsed_tx["token_transfers"] = json!(token_transfers);
-            }
-        }
+            _ => None,
+        };
 
-        Ok(parsed_tx)
+        Ok(ScannedTransaction {
+            chain: self.chain_name().to_string(),
+            event: TransactionEvent::Transaction,
+            tx_hash: tx_id.to_string(),
+            block_number,
+            block_hash: None,
+            from,
+            to,
+            value,
+            status,
+            confirmations: 0,
+            contract_address,
+            token_transfers,
+            details: ChainDetails::Tron(Box::new(TronDetails {
+                contract_type,
+                contract: decoded,
+                token_call,
+                resources,
+                receipt_result,
+            })),
+        })
     }
 
     fn get_start_block(&self) -> u64 {
         self.start_block
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanners::watchlist::AddressFormat;
+    use crate::storage::token_metadata::TokenMetadataStore;
+    use crate::utils::test_http;
+    use num_bigint::BigUint;
+
+    const CONTRACT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+    const SENDER_HEX: &str = "a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+    const RECEIVER_HEX: &str = "4e83362442b8d1bec281594cea3050c8eb01311c";
+
+    fn transfer_call(value: u64) -> DecodedTokenCall {
+        DecodedTokenCall {
+            method: TokenCallMethod::Transfer,
+            from: None,
+            to: RECEIVER_HEX.to_string(),
+            value: BigUint::from(value),
+        }
+    }
+
+    fn transfer_log(value: u64) -> TokenTransfer {
+        TokenTransfer {
+            event_type: TokenEventType::Transfer,
+            standard: TokenStandard::Trc20,
+            contract_address: CONTRACT.to_string(),
+            log_index: Some(0),
+            from: hex_to_tron_address(SENDER_HEX).unwrap(),
+            to: hex_to_tron_address(RECEIVER_HEX).unwrap(),
+            value: value.to_string(),
+            token_id: None,
+            symbol: None,
+            decimals: None,
+            amount: None,
+        }
+    }
+
+    fn build(
+        call: DecodedTokenCall,
+        result: &str,
+        logs: Option<&[TokenTransfer]>,
+    ) -> Result<TokenCall, AppError> {
+        let sender = hex_to_tron_address(SENDER_HEX).unwrap();
+        build_token_call(call, CONTRACT, &sender, &Some(result.to_string()), logs)
+    }
+
+    #[test]
+    fn successful_transfer_with_matching_log() {
+        let call = build(transfer_call(100), "SUCCESS", Some(&[transfer_log(100)])).unwrap();
+        assert_eq!(call.method, TokenCallMethod::Transfer);
+        assert_eq!(call.from, hex_to_tron_address(SENDER_HEX).unwrap());
+        assert_eq!(call.to, hex_to_tron_address(RECEIVER_HEX).unwrap());
+        assert_eq!(call.value, "100");
+        assert_eq!(call.result.as_deref(), Some("SUCCESS"));
+        assert!(!call.log_mismatch);
+    }
+
+    #[test]
+    fn successful_transfer_without_matching_log_is_a_mismatch() {
+        assert!(
+            build(transfer_call(100), "SUCCESS", Some(&[]))
+                .unwrap()
+                .log_mismatch
+        );
+        // 扣手续费的代币：日志数量少于调用数量
+        assert!(
+            build(transfer_call(100), "SUCCESS", Some(&[transfer_log(99)]))
+                .unwrap()
+                .log_mismatch
+        );
+    }
+
+    #[test]
+    fn reverted_transfer_is_reported_with_its_result() {
+        let call = build(transfer_call(100), "REVERT", Some(&[])).unwrap();
+        assert_eq!(call.result.as_deref(), Some("REVERT"));
+        assert!(!call.log_mismatch);
+
+        // 回滚的交易不应该有 Transfer 日志
+        assert!(
+            build(transfer_call(100), "REVERT", Some(&[transfer_log(100)]))
+                .unwrap()
+                .log_mismatch
+        );
+    }
+
+    #[test]
+    fn approve_and_missing_info_are_never_mismatches() {
+        let approve = DecodedTokenCall {
+            method: TokenCallMethod::Approve,
+            ..transfer_call(100)
+        };
+        assert!(!build(approve, "SUCCESS", Some(&[])).unwrap().log_mismatch);
+        assert!(
+            !build(transfer_call(100), "SUCCESS", None)
+                .unwrap()
+                .log_mismatch
+        );
+    }
+
+    #[test]
+    fn transfer_from_uses_the_decoded_owner() {
+        let call = DecodedTokenCall {
+            method: TokenCallMethod::TransferFrom,
+            from: Some(RECEIVER_HEX.to_string()),
+            to: SENDER_HEX.to_string(),
+            value: BigUint::from(5u8),
+        };
+        let call = build(call, "SUCCESS", Some(&[])).unwrap();
+        assert_eq!(call.from, hex_to_tron_address(RECEIVER_HEX).unwrap());
+        assert_eq!(call.to, hex_to_tron_address(SENDER_HEX).unwrap());
+        assert!(call.log_mismatch);
+    }
+
+    /// USDT 转账，没有质押能量，全部燃烧 TRX 支付，并有动态能量惩罚
+    #[test]
+    fn trc20_transfer_burning_energy_with_penalty() {
+        let info = json!({
+            "id": "7c2d4206c03a883dd9066d6c839d0deaef32dc5a0d9b15f6d06e506906c90332",
+            "fee": 13844850,
+            "blockNumber": 65766023,
+            "blockTimeStamp": 1726000000000u64,
+            "contractResult": [""],
+            "contract_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            "receipt": {
+                "energy_fee": 13499850,
+                "energy_usage_total": 64285,
+                "net_fee": 345000,
+                "result": "SUCCESS",
+                "energy_penalty_total": 49635
+            }
+        });
+        let resources = parse_resources(&info);
+        assert_eq!(resources.bandwidth_used, 0);
+        assert_eq!(resources.bandwidth_fee_sun, 345000);
+        assert_eq!(resources.energy_used, 64285);
+        assert_eq!(resources.energy_from_stake, 0);
+        assert_eq!(resources.energy_from_origin, 0);
+        assert_eq!(resources.energy_burned, 64285);
+        assert_eq!(resources.energy_fee_sun, 13499850);
+        assert_eq!(resources.energy_penalty, 49635);
+        assert_eq!(resources.other_fee_sun, 0);
+        assert_eq!(resources.total_fee_sun, 13844850);
+        assert_eq!(resources.total_fee_trx, "13.84485");
+    }
+
+    /// 质押能量不足，部分由合约部署者承担，其余燃烧 TRX
+    #[test]
+    fn trc20_transfer_with_staked_and_origin_energy() {
+        let info = json!({
+            "fee": 8249850,
+            "receipt": {
+                "energy_usage": 20000,
+                "origin_energy_usage": 5000,
+                "energy_fee": 8249850,
+                "energy_usage_total": 64285,
+                "net_usage": 345,
+                "result": "SUCCESS"
+            }
+        });
+        let resources = parse_resources(&info);
+        assert_eq!(resources.bandwidth_used, 345);
+        assert_eq!(resources.bandwidth_fee_sun, 0);
+        assert_eq!(resources.energy_from_stake, 20000);
+        assert_eq!(resources.energy_from_origin, 5000);
+        assert_eq!(resources.energy_burned, 39285);
+        assert_eq!(resources.total_fee_trx, "8.24985");
+    }
+
+    /// 向未激活地址转 TRX：1 TRX 激活费加上燃烧的带宽
+    #[test]
+    fn account_activation_fee_is_reported_separately() {
+        let info = json!({
+            "id": "b7e2fbc7c0b3a1c7d1f2a0b3f4e5d6c7b8a9f0e1d2c3b4a5968778695a4b3c2d",
+            "fee": 1100000,
+            "blockNumber": 65766023,
+            "receipt": { "net_fee": 100000 }
+        });
+        let resources = parse_resources(&info);
+        assert_eq!(resources.bandwidth_fee_sun, 100000);
+        assert_eq!(resources.other_fee_sun, 1000000);
+        assert_eq!(resources.total_fee_sun, 1100000);
+        assert_eq!(resources.total_fee_trx, "1.1");
+    }
+
+    #[test]
+    fn transaction_without_info_costs_nothing() {
+        let resources = parse_resources(&Value::Null);
+        assert_eq!(resources.bandwidth_used, 0);
+        assert_eq!(resources.energy_used, 0);
+        assert_eq!(resources.total_fee_sun, 0);
+        assert_eq!(resources.total_fee_trx, "0");
+    }
+
+    #[test]
+    fn oversized_node_values_saturate() {
+        let info = json!({
+            "receipt": {
+                "net_fee": u64::MAX,
+                "energy_fee": 1,
+                "energy_usage": u64::MAX,
+                "origin_energy_usage": 1,
+                "energy_usage_total": 10
+            }
+        });
+        let resources = parse_resources(&info);
+        assert_eq!(resources.total_fee_sun, u64::MAX);
+        assert_eq!(resources.other_fee_sun, 0);
+        assert_eq!(resources.energy_burned, 0);
+    }
+
+    const BLOCK_NUM: u64 = 65768327;
+    const TRX_TX: &str = "e9a13b79a5ee6e8d3ee3d0b1f4e7c7c3f2a9d1b8c4e6f0a2b5d7c9e1f3a5b7d9";
+    const TRC10_TX: &str = "4c1b0e6a9f3d2c8b7a6e5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a";
+
+    /// getblockbynum 的返回：一笔 TRX 转账和一笔 TRC-10 转账
+    fn block_fixture() -> Value {
+        let sender = format!("41{}", SENDER_HEX);
+        let receiver = format!("41{}", RECEIVER_HEX);
+        json!({
+            "blockID": "0000000003eb8b87c3a2f1e0d9c8b7a6f5e4d3c2b1a0f9e8d7c6b5a4f3e2d1c0",
+            "block_header": {
+                "raw_data": {
+                    "number": BLOCK_NUM,
+                    "txTrieRoot": "5d0e3f2a1b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e",
+                    "witness_address": "41b487cdc02de90f15ac89a68c82f44cbfe3d915ea",
+                    "parentHash": "0000000003eb8b86a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718",
+                    "version": 30,
+                    "timestamp": 1726000000000u64
+                },
+                "witness_signature": "9f1c"
+            },
+            "transactions": [
+                {
+                    "ret": [{ "contractRet": "SUCCESS" }],
+                    "signature": ["3b7e"],
+                    "txID": TRX_TX,
+                    "raw_data": {
+                        "contract": [{
+                            "parameter": {
+                                "value": {
+                                    "amount": 2500000,
+                                    "owner_address": sender,
+                                    "to_address": receiver
+                                },
+                                "type_url": "type.googleapis.com/protocol.TransferContract"
+                            },
+                            "type": "TransferContract"
+                        }],
+                        "ref_block_bytes": "8b75",
+                        "ref_block_hash": "0b1d3e5f7a9c2e4f",
+                        "expiration": 1726000057000u64,
+                        "timestamp": 1725999998000u64
+                    },
+                    "raw_data_hex": "0a028b75"
+                },
+                {
+                    "ret": [{ "contractRet": "SUCCESS" }],
+                    "signature": ["8c2d"],
+                    "txID": TRC10_TX,
+                    "raw_data": {
+                        "contract": [{
+                            "parameter": {
+                                "value": {
+                                    "amount": 1000,
+                                    "asset_name": "31303032303030",
+                                    "owner_address": sender,
+                                    "to_address": receiver
+                                },
+                                "type_url": "type.googleapis.com/protocol.TransferAssetContract"
+                            },
+                            "type": "TransferAssetContract"
+                        }],
+                        "ref_block_bytes": "8b75",
+                        "ref_block_hash": "0b1d3e5f7a9c2e4f",
+                        "expiration": 1726000057000u64,
+                        "timestamp": 1725999998000u64
+                    },
+                    "raw_data_hex": "0a028b76"
+                }
+            ]
+        })
+    }
+
+    /// gettransactioninfobyblocknum 中使用质押带宽的转账只有 net_usage
+    fn info_fixture(tx_id: &str, net_usage: u64) -> Value {
+        json!({
+            "id": tx_id,
+            "blockNumber": BLOCK_NUM,
+            "blockTimeStamp": 1726000000000u64,
+            "contractResult": [""],
+            "receipt": { "net_usage": net_usage }
+        })
+    }
+
+    /// 模拟节点按路径返回区块和交易信息
+    async fn test_scanner(block: Value, infos: Value) -> TronScanner {
+        let url = test_http::serve(move |request| {
+            assert_eq!(request.json()["num"], BLOCK_NUM);
+            match request.path.as_str() {
+                "/wallet/getblockbynum" => (200, block.to_string()),
+                "/wallet/gettransactioninfobyblocknum" => (200, infos.to_string()),
+                _ => (404, String::new()),
+            }
+        })
+        .await;
+
+        let config: ChainConfig = serde_json::from_value(json!({
+            "kind": "tron",
+            "name": "tron",
+            "api_url": url,
+            "start_block": 0
+        }))
+        .unwrap();
+        let options: TronOptions = config.options().unwrap();
+        let pool = crate::storage::connect_in_memory().await;
+        let tokens = Arc::new(TokenRegistry::new(
+            "tron",
+            TokenMetadataStore::new(pool).await.unwrap(),
+        ));
+        let watchlist = Arc::new(Watchlist::new(
+            "tron",
+            AddressFormat::Tron,
+            &config.watchlist,
+        ));
+        TronScanner::new(&config, &options, watchlist, tokens).unwrap()
+    }
+
+    #[tokio::test]
+    async fn joins_block_transactions_with_their_infos() {
+        // 交易信息的顺序不一定与区块中的交易相同
+        let infos = json!([info_fixture(TRC10_TX, 283), info_fixture(TRX_TX, 268)]);
+        let scanner = test_scanner(block_fixture(), infos).await;
+
+        let block = scanner.scan_block(BLOCK_NUM).await.unwrap();
+        assert_eq!(block.number, BLOCK_NUM);
+        assert_eq!(
+            block.parent_hash,
+            "0000000003eb8b86a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718"
+        );
+        assert_eq!(block.transactions.len(), 2);
+
+        let sender = hex_to_tron_address(SENDER_HEX).unwrap();
+        let receiver = hex_to_tron_address(RECEIVER_HEX).unwrap();
+
+        let trx = &block.transactions[0];
+        assert_eq!(trx.tx_hash, TRX_TX);
+        assert_eq!(trx.block_number, BLOCK_NUM);
+        assert_eq!(trx.block_hash.as_deref(), Some(block.hash.as_str()));
+        assert_eq!(trx.from.as_deref(), Some(sender.as_str()));
+        assert_eq!(trx.to.as_deref(), Some(receiver.as_str()));
+        assert_eq!(trx.value.as_deref(), Some("2500000"));
+        assert_eq!(trx.status, TransactionStatus::Success);
+        assert!(trx.token_transfers.is_empty());
+        let ChainDetails::Tron(details) = &trx.details else {
+            panic!("expected TRON details");
+        };
+        assert_eq!(details.contract_type, "TransferContract");
+        assert_eq!(details.resources.bandwidth_used, 268);
+        assert_eq!(details.receipt_result.as_deref(), Some("SUCCESS"));
+
+        let trc10 = &block.transactions[1];
+        assert_eq!(trc10.tx_hash, TRC10_TX);
+        assert_eq!(trc10.value, None);
+        assert_eq!(trc10.token_transfers.len(), 1);
+        let transfer = &trc10.token_transfers[0];
+        assert_eq!(transfer.event_type, TokenEventType::AssetTransfer);
+        assert_eq!(transfer.standard, TokenStandard::Trc10);
+        assert_eq!(transfer.contract_address, "1002000");
+        assert_eq!(transfer.log_index, None);
+        assert_eq!(transfer.from, sender);
+        assert_eq!(transfer.to, receiver);
+        assert_eq!(transfer.value, "1000");
+        let ChainDetails::Tron(details) = &trc10.details else {
+            panic!("expected TRON details");
+        };
+        assert_eq!(details.resources.bandwidth_used, 283);
+    }
+
+    #[tokio::test]
+    async fn missing_transaction_info_fails_the_block() {
+        let infos = json!([info_fixture(TRX_TX, 268)]);
+        let scanner = test_scanner(block_fixture(), infos).await;
+        let error = scanner.scan_block(BLOCK_NUM).await.unwrap_err();
+        assert!(error.to_string().contains(TRC10_TX), "{}", error);
+
+        // 落后的节点对非空区块返回空列表
+        let scanner = test_scanner(block_fixture(), json!([])).await;
+        assert!(scanner.scan_block(BLOCK_NUM).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn non_array_transaction_info_fails_the_block() {
+        let infos = json!({ "Error": "class java.lang.NullPointerException : null" });
+        let scanner = test_scanner(block_fixture(), infos).await;
+        assert!(matches!(
+            scanner.scan_block(BLOCK_NUM).await,
+            Err(AppError::ApiError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn empty_block_has_no_transactions() {
+        let mut block = block_fixture();
+        block.as_object_mut().unwrap().remove("transactions");
+        let scanner = test_scanner(block, json!([])).await;
+        assert!(scanner
+            .scan_block(BLOCK_NUM)
+            .await
+            .unwrap()
+            .transactions
+            .is_empty());
+    }
+
+    /// raw_data.contract[0] 的节点原文（visible=false，地址为 41 开头的十六进制）
+    fn node_contract(contract_type: &str, value: Value) -> Value {
+        json!({
+            "parameter": {
+                "value": value,
+                "type_url": format!("type.googleapis.com/protocol.{}", contract_type)
+            },
+            "type": contract_type
+        })
+    }
+
+    #[test]
+    fn decodes_contract_parameters() {
+        let owner = format!("41{}", SENDER_HEX);
+        let other = format!("41{}", RECEIVER_HEX);
+        let other_base58 = hex_to_tron_address(RECEIVER_HEX).unwrap();
+        let transfer_data = format!(
+            "a9059cbb000000000000000000000000{}00000000000000000000000000000000000000000000000000000000000f4240",
+            RECEIVER_HEX
+        );
+
+        let cases = [
+            (
+                node_contract(
+                    "TriggerSmartContract",
+                    json!({
+                        "data": transfer_data,
+                        "owner_address": owner,
+                        "contract_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c"
+                    }),
+                ),
+                json!({ "trigger_smart_contract": {
+                    "contract_address": CONTRACT,
+                    "call_value": 0,
+                    "call_token_value": 0,
+                    "token_id": null,
+                    "data": transfer_data
+                }}),
+            ),
+            (
+                // 随调用转入 TRX 和 TRC-10
+                node_contract(
+                    "TriggerSmartContract",
+                    json!({
+                        "owner_address": owner,
+                        "contract_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                        "call_value": 1000000,
+                        "call_token_value": 5,
+                        "token_id": 1002000
+                    }),
+                ),
+                json!({ "trigger_smart_contract": {
+                    "contract_address": CONTRACT,
+                    "call_value": 1000000,
+                    "call_token_value": 5,
+                    "token_id": "1002000",
+                    "data": null
+                }}),
+            ),
+            (
+                node_contract(
+                    "FreezeBalanceV2Contract",
+                    json!({ "frozen_balance": 100000000, "resource": "ENERGY", "owner_address": owner }),
+                ),
+                json!({ "freeze_balance_v2": { "resource": "ENERGY", "amount": 100000000 } }),
+            ),
+            (
+                // 质押带宽时节点省略 resource
+                node_contract(
+                    "FreezeBalanceV2Contract",
+                    json!({ "frozen_balance": 2000000, "owner_address": owner }),
+                ),
+                json!({ "freeze_balance_v2": { "resource": "BANDWIDTH", "amount": 2000000 } }),
+            ),
+            (
+                node_contract(
+                    "UnfreezeBalanceV2Contract",
+                    json!({ "unfreeze_balance": 50000000, "resource": "TRON_POWER", "owner_address": owner }),
+                ),
+                json!({ "unfreeze_balance_v2": { "resource": "TRON_POWER", "amount": 50000000 } }),
+            ),
+            (
+                node_contract(
+                    "DelegateResourceContract",
+                    json!({
+                        "balance": 1000000000,
+                        "resource": "ENERGY",
+                        "receiver_address": other,
+                        "lock": true,
+                        "lock_period": 86400,
+                        "owner_address": owner
+                    }),
+                ),
+                json!({ "delegate_resource": {
+                    "resource": "ENERGY",
+                    "amount": 1000000000,
+                    "receiver": other_base58,
+                    "lock": true,
+                    "lock_period": 86400
+                }}),
+            ),
+            (
+                node_contract(
+                    "UnDelegateResourceContract",
+                    json!({ "balance": 300000000, "receiver_address": other, "owner_address": owner }),
+                ),
+                json!({ "un_delegate_resource": {
+                    "resource": "BANDWIDTH",
+                    "amount": 300000000,
+                    "receiver": other_base58
+                }}),
+            ),
+            (
+                node_contract(
+                    "VoteWitnessContract",
+                    json!({
+                        "owner_address": owner,
+                        "votes": [
+                            { "vote_address": other, "vote_count": 120 },
+                            { "vote_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c", "vote_count": 3 }
+                        ]
+                    }),
+                ),
+                json!({ "vote_witness": { "votes": [
+                    { "witness": other_base58, "count": 120 },
+                    { "witness": CONTRACT, "count": 3 }
+                ]}}),
+            ),
+            (
+                node_contract(
+                    "AccountCreateContract",
+                    json!({ "account_address": other, "owner_address": owner }),
+                ),
+                json!({ "account_create": { "account_address": other_base58 } }),
+            ),
+            (
+                node_contract(
+                    "CreateSmartContract",
+                    json!({
+                        "owner_address": owner,
+                        "new_contract": {
+                            "bytecode": "608060405234801561001057600080fd5b50",
+                            "consume_user_resource_percent": 100,
+                            "name": "TetherToken",
+                            "origin_address": owner,
+                            "abi": {},
+                            "origin_energy_limit": 10000000
+                        }
+                    }),
+                ),
+                json!({ "create_smart_contract": {
+                    "name": "TetherToken",
+                    "call_value": 0,
+                    "consume_user_resource_percent": 100,
+                    "origin_energy_limit": 10000000
+                }}),
+            ),
+            (
+                node_contract(
+                    "AccountPermissionUpdateContract",
+                    json!({ "owner_address": owner }),
+                ),
+                json!("other"),
+            ),
+        ];
+
+        for (contract, expected) in cases {
+            let decoded = serde_json::to_value(decode_contract(&contract)).unwrap();
+            assert_eq!(decoded, expected, "{}", contract["type"]);
+        }
+    }
+
+    #[test]
+    fn invalid_address_is_an_error() {
+        let call = DecodedTokenCall {
+            to: "zz".repeat(20),
+            ..transfer_call(100)
+        };
+        assert!(build(call, "SUCCESS", Some(&[])).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::utils::abi::TokenCallMethod;
+use serde::{Deserialize, Serialize};
+
+/// 扫描器输出的统一交易结构，所有链共用同一套字段，链特有的字段放在 details 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannedTransaction {
+    pub chain: String,
+    pub event: TransactionEvent,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub block_hash: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// 原生币数量，最小单位的整数字符串（wei / sun）
+    pub value: Option<String>,
+    pub status: TransactionStatus,
+    /// latest_block - block_number，由扫描循环根据链头填写
+    pub confirmations: u64,
+    pub contract_address: Option<String>,
+    pub token_transfers: Vec<TokenTransfer>,
+    #[serde(flatten)]
+    pub details: ChainDetails,
+}
+
+impl ScannedTransaction {
+    /// 把已发出的交易转换为 revert 事件，下游据此撤销孤块中的交易
+    pub fn into_revert(mut self) -> Self {
+        self.event = TransactionEvent::Revert;
+        self
+    }
+}
+
+/// scan_block 的结果：区块中的全部交易及检测链重组用的区块头
+#[derive(Debug, Clone)]
+pub struct ScannedBlock {
+    pub number: u64,
+    pub hash: String,
+    pub parent_hash: String,
+    pub transactions: Vec<ScannedTransaction>,
+}
+
+/// reconcile_block 的结果，事件投递成功后交给 commit_block 记录
+#[derive(Debug, Clone)]
+pub struct ReconciledBlock {
+    /// 按顺序发出的事件：孤块交易的 revert、重新扫描的规范链区块中的交易、本区块的交易
+    pub events: Vec<ScannedTransaction>,
+    /// 被链重组废弃的区块号
+    pub orphaned: Vec<u64>,
+    /// 提交时记录的区块：重新扫描的规范链区块和本区块
+    pub blocks: Vec<ScannedBlock>,
+}
+
+impl ReconciledBlock {
+    /// 没有链重组时只包含区块本身
+    pub fn new(block: ScannedBlock) -> Self {
+        ReconciledBlock {
+            events: block.transactions.clone(),
+            orphaned: Vec::new(),
+            blocks: vec![block],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionEvent {
+    /// 新扫描到的交易
+    Transaction,
+    /// 所在区块因链重组成为孤块，之前发出的交易需要撤销
+    Revert,
+}
+
+impl TransactionEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionEvent::Transaction => "transaction",
+            TransactionEvent::Revert => "revert",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Success,
+    Failed,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenTransfer {
+    pub event_type: TokenEventType,
+    pub standard: TokenStandard,
+    /// 发出事件的代币合约地址（EVM 链为 0x 十六进制，TRON 为 Base58）；TRC-10 为 token id
+    pub contract_address: String,
+    /// Transfer 日志的序号：EVM 链为回执中区块内的 logIndex（缺失时为日志在回执中的位置），TRON 为日志在交易 log 列表中的位置；
+    /// 不来自日志的转账（TRC-10）为 None
+    pub log_index: Option<u64>,
+    pub from: String,
+    pub to: String,
+    /// 代币数量，最小单位的整数字符串；NFT 固定为 "1"
+    pub value: String,
+    /// NFT 的 tokenId，同质化代币为 None
+    pub token_id: Option<String>,
+    /// 以下字段来自代币注册表，无法解析合约元数据时为 None
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    /// 按 decimals 换算后的十进制数量，例如 "12.5"
+    pub amount: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenEventType {
+    /// 合约发出的 Transfer 事件日志
+    Transfer,
+    /// TRON TransferAssetContract 交易转出的 TRC-10 资产
+    AssetTransfer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenStandard {
+    Erc20,
+    Erc721,
+    Trc10,
+    Trc20,
+    Trc721,
+}
+
+/// 链特有的字段，序列化为 "evm": {...} 或 "tron": {...}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainDetails {
+    Evm(EvmDetails),
+    /// 解码后的合约参数较大，装箱以免放大所有交易的体积
+    Tron(Box<TronDetails>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvmDetails {
+    pub chain_id: u64,
+    /// 原生币符号，例如 BNB、ETH、POL，value 以其最小单位计
+    pub native_symbol: String,
+    pub gas_used: u64,
+    pub is_contract: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TronDetails {
+    /// 节点返回的原始合约类型，例如 TransferContract、TriggerSmartContract
+    pub contract_type: String,
+    pub contract: TronContract,
+    /// TriggerSmartContract 调用的 TRC-20 方法，不论交易成功与否都会解码
+    pub token_call: Option<TokenCall>,
+    pub resources: TronResources,
+    /// 回执中的原始结果，例如 SUCCESS、REVERT、OUT_OF_ENERGY
+    pub receipt_result: Option<String>,
+}
+
+/// TRON 交易中合约参数的解码结果，序列化为 {"trigger_smart_contract": {...}}；
+/// 地址为 Base58，数量以 sun 计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TronContract {
+    Transfer {
+        amount: u64,
+    },
+    TransferAsset {
+        /// TRC-10 token id
+        asset_id: String,
+        amount: u64,
+    },
+    TriggerSmartContract {
+        contract_address: String,
+        call_value: u64,
+        /// 随调用转入的 TRC-10 数量及其 token id
+        call_token_value: u64,
+        token_id: Option<String>,
+        /// 十六进制调用数据（不含 0x），前 4 字节为方法选择器
+        data: Option<String>,
+    },
+    FreezeBalanceV2 {
+        /// BANDWIDTH、ENERGY 或 TRON_POWER
+        resource: String,
+        amount: u64,
+    },
+    UnfreezeBalanceV2 {
+        resource: String,
+        amount: u64,
+    },
+    DelegateResource {
+        resource: String,
+        amount: u64,
+        receiver: String,
+        lock: bool,
+        /// 锁定的区块数，未锁定为 0
+        lock_period: u64,
+    },
+    UnDelegateResource {
+        resource: String,
+        amount: u64,
+        receiver: String,
+    },
+    VoteWitness {
+        votes: Vec<TronVote>,
+    },
+    AccountCreate {
+        account_address: String,
+    },
+    CreateSmartContract {
+        name: Option<String>,
+        call_value: u64,
+        consume_user_resource_percent: Option<u64>,
+        origin_energy_limit: Option<u64>,
+    },
+    /// 未单独解码的合约类型，见 contract_type
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TronVote {
+    pub witness: String,
+    pub count: u64,
+}
+
+/// 从调用数据解码出的 TRC-20 transfer / transferFrom / approve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenCall {
+    pub method: TokenCallMethod,
+    pub contract_address: String,
+    /// 转出地址，approve 为授权人
+    pub from: String,
+    /// 收款地址，approve 为被授权地址
+    pub to: String,
+    pub value: String,
+    /// 回执结果，例如 SUCCESS、REVERT、OUT_OF_ENERGY
+    pub result: Option<String>,
+    /// 调用数据与 Transfer 日志不一致：成功的转账没有对应的日志，或日志的地址、数量不同
+    pub log_mismatch: bool,
+}
+
+/// 交易的资源消耗与费用明细，来自交易信息的 fee 和 receipt；sun 为 TRX 的最小单位
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TronResources {
+    /// 由质押或每日免费额度抵扣的带宽（字节）
+    pub bandwidth_used: u64,
+    /// 带宽不足时燃烧的 TRX（receipt.net_fee）
+    pub bandwidth_fee_sun: u64,
+    /// 合约执行消耗的全部能量，包含动态能量惩罚和合约部署者承担的部分
+    pub energy_used: u64,
+    /// 调用者质押抵扣的能量（receipt.energy_usage）
+    pub energy_from_stake: u64,
+    /// 合约部署者按比例承担的能量（receipt.origin_energy_usage）
+    pub energy_from_origin: u64,
+    /// 调用者通过燃烧 TRX 支付的能量
+    pub energy_burned: u64,
+    /// 为能量燃烧的 TRX（receipt.energy_fee）
+    pub energy_fee_sun: u64,
+    /// 热门合约的动态能量惩罚，已包含在 energy_used 中
+    pub energy_penalty: u64,
+    /// 带宽和能量之外的费用，例如激活账户、多签、备注
+    pub other_fee_sun: u64,
+    /// 调用者支付的全部费用
+    pub total_fee_sun: u64,
+    /// total_fee_sun 换算为 TRX 的十进制字符串，例如 "13.3959"
+    pub total_fee_trx: String,
+}
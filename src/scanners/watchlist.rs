@@ -0,0 +1,281 @@
+use crate::scanners::types::{ChainDetails, ScannedTransaction};
+use crate::storage::watchlist::WatchlistStore;
+use crate::utils::config::WatchlistConfig;
+use crate::utils::error::AppError;
+use crate::utils::tron::tron_address_to_hex;
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+/// 链上地址的表示方式，决定地址如何归一化后再比较
+#[derive(Debug, Clone, Copy)]
+pub enum AddressFormat {
+    /// 0x 开头的十六进制地址，比较时忽略大小写
+    Evm,
+    /// Base58 (T...) 或十六进制 (41... / 0x...) 地址，统一转换为 41 开头的十六进制
+    Tron,
+}
+
+impl AddressFormat {
+    pub fn normalize(&self, address: &str) -> Option<String> {
+        let address = address.trim();
+        match self {
+            AddressFormat::Evm => {
+                let hex = address.trim_start_matches("0x").to_lowercase();
+                (hex.len() == 40).then(|| format!("0x{}", hex))
+            }
+            AddressFormat::Tron => {
+                if address.starts_with('T') {
+                    return tron_address_to_hex(address).ok();
+                }
+                let hex = address.trim_start_matches("0x").to_lowercase();
+                match hex.len() {
+                    40 => Some(format!("41{}", hex)),
+                    42 if hex.starts_with("41") => Some(hex),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// 单条链的监控地址列表，可以在运行时从文件和数据库重新加载
+pub struct Watchlist {
+    chain: String,
+    format: AddressFormat,
+    config: WatchlistConfig,
+    addresses: RwLock<HashSet<String>>,
+}
+
+impl Watchlist {
+    pub fn new(chain: &str, format: AddressFormat, config: &WatchlistConfig) -> Self {
+        let addresses = config
+            .addresses
+            .iter()
+            .filter_map(|address| format.normalize(address))
+            .collect();
+
+        Watchlist {
+            chain: chain.to_string(),
+            format,
+            config: config.clone(),
+            addresses: RwLock::new(addresses),
+        }
+    }
+
+    pub fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enable
+    }
+
+    pub fn reload_seconds(&self) -> u64 {
+        self.config.reload_seconds
+    }
+
+    /// 合并配置、文件和数据库中的地址，整体替换当前列表，返回地址数量
+    pub async fn reload(&self, store: &WatchlistStore) -> Result<usize, AppError> {
+        let mut raw_addresses = self.config.addresses.clone();
+
+        if let Some(file) = &self.config.file {
+            let content = tokio::fs::read_to_string(file).await?;
+            raw_addresses.extend(
+                content
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.to_string()),
+            );
+        }
+
+        raw_addresses.extend(store.load(&self.chain).await?);
+
+        let mut addresses = HashSet::new();
+        for address in raw_addresses {
+            match self.format.normalize(&address) {
+                Some(normalized) => {
+                    addresses.insert(normalized);
+                }
+                None => tracing::warn!("Ignoring invalid {} address: {}", self.chain, address),
+            }
+        }
+
+        let count = addresses.len();
+        *self.addresses.write().unwrap() = addresses;
+        Ok(count)
+    }
+
+    /// 未开启时原样返回，否则只保留涉及监控地址的交易
+    pub fn filter(&self, transactions: Vec<ScannedTransaction>) -> Vec<ScannedTransaction> {
+        if !self.is_enabled() {
+            return transactions;
+        }
+
+        let addresses = self.addresses.read().unwrap();
+        transactions
+            .into_iter()
+            .filter(|tx| self.matches(&addresses, tx))
+            .collect()
+    }
+
+    fn matches(&self, addresses: &HashSet<String>, tx: &ScannedTransaction) -> bool {
+        let is_watched = |address: Option<&str>| {
+            address
+                .and_then(|a| self.format.normalize(a))
+                .is_some_and(|a| addresses.contains(&a))
+        };
+
+        if is_watched(tx.from.as_deref())
+            || is_watched(tx.to.as_deref())
+            || is_watched(tx.contract_address.as_deref())
+        {
+            return true;
+        }
+
+        // 失败的 TRC-20 转账没有日志，只能从调用数据中匹配
+        if let ChainDetails::Tron(details) = &tx.details {
+            if let Some(call) = &details.token_call {
+                if is_watched(Some(&call.from)) || is_watched(Some(&call.to)) {
+                    return true;
+                }
+            }
+        }
+
+        tx.token_transfers.iter().any(|transfer| {
+            is_watched(Some(&transfer.from))
+                || is_watched(Some(&transfer.to))
+                || is_watched(Some(&transfer.contract_address))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanners::types::{
+        TokenEventType, TokenStandard, TokenTransfer, TransactionEvent, TransactionStatus,
+        TronContract, TronDetails, TronResources,
+    };
+
+    const USDT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+    const USDT_HEX: &str = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+    const RECEIVER: &str = "TH48niZfbwHMyqZwEB8wmHfzcvR8ZzJKC6";
+
+    fn watchlist(addresses: &[&str]) -> Watchlist {
+        let config = WatchlistConfig {
+            enable: true,
+            addresses: addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
+            file: None,
+            reload_seconds: 60,
+        };
+        Watchlist::new("TRON", AddressFormat::Tron, &config)
+    }
+
+    fn transaction(tx_hash: &str, token_transfers: Vec<TokenTransfer>) -> ScannedTransaction {
+        ScannedTransaction {
+            chain: "TRON".to_string(),
+            event: TransactionEvent::Transaction,
+            tx_hash: tx_hash.to_string(),
+            block_number: 1,
+            block_hash: None,
+            from: Some("TNPeeaaFB7K9cmo4uQpcU32zGK8G1NYqeL".to_string()),
+            to: Some("TLa2f6VPqDgRE67v1736s7bJ8Ray5wYjU7".to_string()),
+            value: Some("0".to_string()),
+            status: TransactionStatus::Success,
+            confirmations: 0,
+            contract_address: None,
+            token_transfers,
+            details: ChainDetails::Tron(Box::new(TronDetails {
+                contract_type: "TriggerSmartContract".to_string(),
+                contract: TronContract::Other,
+                token_call: None,
+                resources: TronResources {
+                    bandwidth_used: 0,
+                    bandwidth_fee_sun: 0,
+                    energy_used: 0,
+                    energy_from_stake: 0,
+                    energy_from_origin: 0,
+                    energy_burned: 0,
+                    energy_fee_sun: 0,
+                    energy_penalty: 0,
+                    other_fee_sun: 0,
+                    total_fee_sun: 0,
+                    total_fee_trx: "0".to_string(),
+                },
+                receipt_result: Some("SUCCESS".to_string()),
+            })),
+        }
+    }
+
+    fn token_transfer(to: &str) -> TokenTransfer {
+        TokenTransfer {
+            event_type: TokenEventType::Transfer,
+            standard: TokenStandard::Trc20,
+            contract_address: "TXLAQ63Xg1NAzckPwKHvzw7CSEmLMEqcdj".to_string(),
+            log_index: Some(0),
+            from: "TNPeeaaFB7K9cmo4uQpcU32zGK8G1NYqeL".to_string(),
+            to: to.to_string(),
+            value: "1000000".to_string(),
+            token_id: None,
+            symbol: None,
+            decimals: None,
+            amount: None,
+        }
+    }
+
+    #[test]
+    fn tron_address_forms_normalize_identically() {
+        let format = AddressFormat::Tron;
+        let expected = Some(USDT_HEX.to_string());
+        assert_eq!(format.normalize(USDT), expected);
+        assert_eq!(format.normalize(USDT_HEX), expected);
+        assert_eq!(format.normalize(&USDT_HEX.to_uppercase()), expected);
+        assert_eq!(format.normalize(&format!("0x{}", &USDT_HEX[2..])), expected);
+        assert_eq!(format.normalize(&format!("  {}\n", USDT)), expected);
+    }
+
+    #[test]
+    fn invalid_tron_addresses_are_rejected() {
+        let format = AddressFormat::Tron;
+        assert_eq!(format.normalize("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u"), None);
+        assert_eq!(
+            format.normalize("42a614f803b6fd780986a42c78ec9c7f77e6ded13c"),
+            None
+        );
+        assert_eq!(format.normalize("0x1234"), None);
+    }
+
+    #[test]
+    fn filter_matches_token_transfer_parties() {
+        // 监控地址以十六进制配置，日志中的收款地址是 Base58
+        let receiver_hex = tron_address_to_hex(RECEIVER).unwrap();
+        let watchlist = watchlist(&[&receiver_hex]);
+
+        let transactions = vec![
+            transaction("deposit", vec![token_transfer(RECEIVER)]),
+            transaction("unrelated", vec![token_transfer(USDT)]),
+            transaction("no-transfers", Vec::new()),
+        ];
+        let kept: Vec<String> = watchlist
+            .filter(transactions)
+            .into_iter()
+            .map(|tx| tx.tx_hash)
+            .collect();
+        assert_eq!(kept, vec!["deposit"]);
+    }
+
+    #[test]
+    fn disabled_watchlist_keeps_every_transaction() {
+        let mut config = watchlist(&[]).config;
+        config.enable = false;
+        let watchlist = Watchlist::new("TRON", AddressFormat::Tron, &config);
+        assert_eq!(
+            watchlist.filter(vec![transaction("any", Vec::new())]).len(),
+            1
+        );
+    }
+}
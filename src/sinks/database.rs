@@ -0,0 +1,90 @@
+use crate::scanners::types::ScannedTransaction;
+use crate::sinks::TransactionSink;
+use crate::utils::config::DatabaseSinkConfig;
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use sqlx::AnyPool;
+
+/// 写入数据库表，完整交易以 JSON 存在 payload 列中
+pub struct DatabaseSink {
+    pool: AnyPool,
+    insert_sql: String,
+}
+
+impl DatabaseSink {
+    pub async fn new(pool: AnyPool, config: &DatabaseSinkConfig) -> Result<Self, AppError> {
+        let table = &config.table;
+        if !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(AppError::ParseError(format!(
+                "Invalid database sink table name: {}",
+                table
+            )));
+        }
+
+        // 同一交易在重启后可能被重复发送，主键保证幂等；block_hash 区分重组后的重新打包
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                chain TEXT NOT NULL,
+                tx_hash TEXT NOT NULL,
+                event TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_hash TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                created_at BIGINT NOT NULL,
+                PRIMARY KEY (chain, tx_hash, event, block_hash)
+            )",
+            table
+        ))
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let insert_sql = format!(
+            "INSERT INTO {} (chain, tx_hash, event, block_number, block_hash, payload, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (chain, tx_hash, event, block_hash) DO NOTHING",
+            table
+        );
+
+        Ok(DatabaseSink { pool, insert_sql })
+    }
+}
+
+#[async_trait]
+impl TransactionSink for DatabaseSink {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn send(&self, transactions: &[ScannedTransaction]) -> Result<(), AppError> {
+        let mut db_tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        for tx in transactions {
+            let payload =
+                serde_json::to_string(tx).map_err(|e| AppError::JsonParseError(e.to_string()))?;
+
+            sqlx::query(&self.insert_sql)
+                .bind(&tx.chain)
+                .bind(&tx.tx_hash)
+                .bind(tx.event.as_str())
+                .bind(tx.block_number as i64)
+                .bind(tx.block_hash.clone().unwrap_or_default())
+                .bind(payload)
+                .bind(now)
+                .execute(&mut *db_tx)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        db_tx
+            .commit()
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+}
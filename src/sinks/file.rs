@@ -0,0 +1,59 @@
+use crate::scanners::types::ScannedTransaction;
+use crate::sinks::TransactionSink;
+use crate::utils::config::FileSinkConfig;
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+/// 以 NDJSON 写入按时间滚动的文件
+pub struct FileSink {
+    writer: Mutex<RollingFileAppender>,
+}
+
+impl FileSink {
+    pub fn new(config: &FileSinkConfig) -> Result<Self, AppError> {
+        let rotation = match config.rotation.as_str() {
+            "minutely" => Rotation::MINUTELY,
+            "hourly" => Rotation::HOURLY,
+            "daily" => Rotation::DAILY,
+            "never" => Rotation::NEVER,
+            other => {
+                return Err(AppError::ParseError(format!(
+                    "Unknown file sink rotation: {}",
+                    other
+                )))
+            }
+        };
+
+        let dir = Path::new(&config.path);
+        std::fs::create_dir_all(dir)?;
+
+        Ok(FileSink {
+            writer: Mutex::new(RollingFileAppender::new(rotation, dir, &config.file_name)),
+        })
+    }
+}
+
+#[async_trait]
+impl TransactionSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn send(&self, transactions: &[ScannedTransaction]) -> Result<(), AppError> {
+        let mut buf = Vec::new();
+        for tx in transactions {
+            serde_json::to_writer(&mut buf, tx)
+                .map_err(|e| AppError::JsonParseError(e.to_string()))?;
+            buf.push(b'\n');
+        }
+
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(&buf)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+pub mod database;
+pub mod file;
+pub mod stdout;
+pub mod webhook;
+
+use crate::scanners::types::ScannedTransaction;
+use crate::storage::webhook_queue::WebhookQueue;
+use crate::utils::config::SinkConfig;
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use sqlx::AnyPool;
+
+/// 扫描结果的输出目标，每个区块的相关交易会依次发送给所有配置的 sink
+#[async_trait]
+pub trait TransactionSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn send(&self, transactions: &[ScannedTransaction]) -> Result<(), AppError>;
+}
+
+/// 根据配置创建 sink，未配置任何 sink 时默认输出到 stdout
+pub async fn build(
+    configs: &[SinkConfig],
+    pool: &AnyPool,
+) -> Result<Vec<Box<dyn TransactionSink>>, AppError> {
+    if configs.is_empty() {
+        return Ok(vec![Box::new(stdout::StdoutSink)]);
+    }
+
+    let mut sinks: Vec<Box<dyn TransactionSink>> = Vec::new();
+    for config in configs {
+        match config {
+            SinkConfig::Stdout => sinks.push(Box::new(stdout::StdoutSink)),
+            SinkConfig::File(config) => sinks.push(Box::new(file::FileSink::new(config)?)),
+            SinkConfig::Database(config) => sinks.push(Box::new(
+                database::DatabaseSink::new(pool.clone(), config).await?,
+            )),
+            SinkConfig::Webhook(config) => {
+                let queue = WebhookQueue::new(pool.clone()).await?;
+                sinks.push(Box::new(webhook::WebhookSink::new(queue, config).await?))
+            }
+        }
+    }
+
+    Ok(sinks)
+}
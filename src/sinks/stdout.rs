@@ -0,0 +1,26 @@
+use crate::scanners::types::ScannedTransaction;
+use crate::sinks::TransactionSink;
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use std::io::Write;
+
+/// 以 NDJSON（每行一个 JSON 对象）输出到标准输出
+pub struct StdoutSink;
+
+#[async_trait]
+impl TransactionSink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    async fn send(&self, transactions: &[ScannedTransaction]) -> Result<(), AppError> {
+        let mut stdout = std::io::stdout().lock();
+        for tx in transactions {
+            let line =
+                serde_json::to_string(tx).map_err(|e| AppError::JsonParseError(e.to_string()))?;
+            writeln!(stdout, "{}", line)?;
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+}
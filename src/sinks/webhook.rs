@@ -0,0 +1,382 @@
+use crate::scanners::types::ScannedTransaction;
+use crate::sinks::TransactionSink;
+use crate::storage::webhook_queue::{QueuedEvent, WebhookQueue};
+use crate::utils::config::WebhookSinkConfig;
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, warn};
+
+/// 每次从队列取出的事件数量
+const BATCH_SIZE: i64 = 100;
+
+/// 把每笔交易以 JSON POST 到配置的 URL。
+///
+/// 事件先写入持久化队列再由后台任务投递，失败后按指数退避重试，
+/// 因此 webhook 暂时不可用不会阻塞扫块，重启后也不会丢失未送达的事件。
+pub struct WebhookSink {
+    url: String,
+    queue: WebhookQueue,
+    notify: Arc<Notify>,
+}
+
+impl WebhookSink {
+    pub async fn new(queue: WebhookQueue, config: &WebhookSinkConfig) -> Result<Self, AppError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+
+        let notify = Arc::new(Notify::new());
+        let worker = WebhookWorker {
+            url: config.url.clone(),
+            secret: config.secret.clone(),
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+            client,
+            queue: queue.clone(),
+            notify: notify.clone(),
+        };
+        tokio::spawn(worker.run());
+
+        Ok(WebhookSink {
+            url: config.url.clone(),
+            queue,
+            notify,
+        })
+    }
+}
+
+#[async_trait]
+impl TransactionSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, transactions: &[ScannedTransaction]) -> Result<(), AppError> {
+        for tx in transactions {
+            let payload =
+                serde_json::to_string(tx).map_err(|e| AppError::JsonParseError(e.to_string()))?;
+            self.queue
+                .enqueue(&self.url, &event_id(tx), &payload)
+                .await?;
+        }
+        self.notify.notify_one();
+        Ok(())
+    }
+}
+
+/// 事件 ID 由交易唯一确定，接收方可以据此去重
+fn event_id(tx: &ScannedTransaction) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tx.chain.as_bytes());
+    hasher.update(tx.tx_hash.as_bytes());
+    hasher.update(tx.event.as_str().as_bytes());
+    hasher.update(tx.block_hash.as_deref().unwrap_or_default().as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+struct WebhookWorker {
+    url: String,
+    secret: Option<String>,
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    client: Client,
+    queue: WebhookQueue,
+    notify: Arc<Notify>,
+}
+
+impl WebhookWorker {
+    async fn run(self) {
+        loop {
+            match self.queue.due(&self.url, BATCH_SIZE).await {
+                Ok(events) if !events.is_empty() => {
+                    for event in events {
+                        self.process(event).await;
+                    }
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => error!("Error reading webhook queue: {:?}", e),
+            }
+
+            // 有新事件入队时立即唤醒，否则定期检查到期的重试
+            let _ = tokio::time::timeout(Duration::from_secs(1), self.notify.notified()).await;
+        }
+    }
+
+    async fn process(&self, event: QueuedEvent) {
+        let result = match self.deliver(&event).await {
+            Ok(()) => self.queue.delete(&self.url, &event.id).await,
+            Err(e) => {
+                let attempts = event.attempts + 1;
+                let dead = attempts >= self.max_attempts;
+                if dead {
+                    error!(
+                        "Webhook event {} failed {} times, giving up: {}",
+                        event.id, attempts, e
+                    );
+                } else {
+                    warn!(
+                        "Webhook event {} failed (attempt {}): {}",
+                        event.id, attempts, e
+                    );
+                }
+                let next_attempt_at = chrono::Utc::now().timestamp_millis()
+                    + self.backoff(attempts).as_millis() as i64;
+                self.queue
+                    .record_failure(
+                        &self.url,
+                        &event.id,
+                        attempts,
+                        next_attempt_at,
+                        dead,
+                        &e.to_string(),
+                    )
+                    .await
+            }
+        };
+
+        if let Err(e) = result {
+            error!("Error updating webhook queue: {:?}", e);
+        }
+    }
+
+    async fn deliver(&self, event: &QueuedEvent) -> Result<(), AppError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header("X-Event-Id", &event.id);
+
+        if let Some(secret) = &self.secret {
+            request = request.header("X-Signature", sign(secret, event.payload.as_bytes())?);
+        }
+
+        let response = request
+            .body(event.payload.clone())
+            .send()
+            .await
+            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::ApiError(format!(
+                "Webhook HTTP error: {}, body: {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 第 n 次失败后等待 base_delay * 2^(n-1)，不超过 max_delay
+    fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// 计算请求体的 HMAC-SHA256 签名，格式为 sha256=<hex>
+fn sign(secret: &str, body: &[u8]) -> Result<String, AppError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::ParseError(e.to_string()))?;
+    mac.update(body);
+    Ok(format!(
+        "sha256={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanners::types::TransactionEvent;
+    use crate::utils::test_http::{self, TestRequest};
+    use std::sync::Mutex;
+
+    const SECRET: &str = "test-secret";
+
+    async fn test_queue() -> WebhookQueue {
+        let pool = crate::storage::connect_in_memory().await;
+        WebhookQueue::new(pool).await.unwrap()
+    }
+
+    fn test_config(url: &str) -> WebhookSinkConfig {
+        WebhookSinkConfig {
+            url: url.to_string(),
+            secret: Some(SECRET.to_string()),
+            max_attempts: 5,
+            base_delay_ms: 10,
+            max_delay_ms: 50,
+            timeout_seconds: 5,
+        }
+    }
+
+    /// 记录收到的请求，前 failures 个请求返回 500
+    async fn receiver(failures: usize) -> (String, Arc<Mutex<Vec<TestRequest>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let url = test_http::serve(move |request| {
+            let mut requests = recorded.lock().unwrap();
+            requests.push(request);
+            if requests.len() <= failures {
+                (500, "unavailable".to_string())
+            } else {
+                (200, "ok".to_string())
+            }
+        })
+        .await;
+        (url, requests)
+    }
+
+    async fn wait_for_requests(requests: &Mutex<Vec<TestRequest>>, count: usize) {
+        for _ in 0..200 {
+            if requests.lock().unwrap().len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("webhook received fewer than {} requests", count);
+    }
+
+    fn expected_signature(body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    fn transaction() -> ScannedTransaction {
+        serde_json::from_value(serde_json::json!({
+            "chain": "BSC",
+            "event": "transaction",
+            "tx_hash": "0xabc",
+            "block_number": 100,
+            "block_hash": "0xdef",
+            "from": "0x1",
+            "to": "0x2",
+            "value": "1000",
+            "status": "success",
+            "confirmations": 15,
+            "contract_address": null,
+            "token_transfers": [],
+            "evm": { "chain_id": 56, "native_symbol": "BNB", "gas_used": 21000, "is_contract": false }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn signs_exact_body_and_retries_server_errors() {
+        let (url, requests) = receiver(2).await;
+        let queue = test_queue().await;
+        let sink = WebhookSink::new(queue.clone(), &test_config(&url))
+            .await
+            .unwrap();
+
+        let tx = transaction();
+        sink.send(std::slice::from_ref(&tx)).await.unwrap();
+        wait_for_requests(&requests, 3).await;
+
+        {
+            let requests = requests.lock().unwrap();
+            let expected_body = serde_json::to_string(&tx).unwrap();
+            for request in requests.iter() {
+                assert_eq!(request.body, expected_body.as_bytes());
+                assert_eq!(
+                    request.headers.get("x-signature"),
+                    Some(&expected_signature(&request.body))
+                );
+                assert_eq!(request.headers.get("x-event-id"), Some(&event_id(&tx)));
+            }
+        }
+
+        // 成功投递后从队列中删除
+        for _ in 0..40 {
+            if queue.due(&url, BATCH_SIZE).await.unwrap().is_empty() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("delivered event is still queued");
+    }
+
+    #[tokio::test]
+    async fn queued_events_keep_enqueue_order() {
+        let pool = crate::storage::connect_in_memory().await;
+        let queue = WebhookQueue::new(pool.clone()).await.unwrap();
+        let url = "http://receiver";
+
+        // 同一毫秒入队，事件 ID 的字典序与入队顺序相反
+        queue.enqueue(url, "f-revert", "{}").await.unwrap();
+        queue.enqueue(url, "a-transaction", "{}").await.unwrap();
+
+        // 重启后新入队的事件排在仍未送达的事件之后
+        let queue = WebhookQueue::new(pool).await.unwrap();
+        queue.enqueue(url, "0-later", "{}").await.unwrap();
+
+        let ids: Vec<String> = queue
+            .due(url, BATCH_SIZE)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(ids, vec!["f-revert", "a-transaction", "0-later"]);
+    }
+
+    #[tokio::test]
+    async fn delivers_revert_before_reemitted_transaction() {
+        let (url, requests) = receiver(0).await;
+        let sink = WebhookSink::new(test_queue().await, &test_config(&url))
+            .await
+            .unwrap();
+
+        let mut revert = transaction();
+        revert.event = TransactionEvent::Revert;
+        revert.block_hash = Some("0xold".to_string());
+        sink.send(&[revert, transaction()]).await.unwrap();
+        wait_for_requests(&requests, 2).await;
+
+        let events: Vec<String> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| request.json()["event"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(events, vec!["revert", "transaction"]);
+    }
+
+    #[tokio::test]
+    async fn redelivers_queued_events_after_restart() {
+        let (url, requests) = receiver(0).await;
+        let queue = test_queue().await;
+
+        // 上一次运行入队但未送达的事件
+        let payload = serde_json::to_string(&transaction()).unwrap();
+        queue
+            .enqueue(&url, "pending-event", &payload)
+            .await
+            .unwrap();
+
+        let _sink = WebhookSink::new(queue.clone(), &test_config(&url))
+            .await
+            .unwrap();
+        wait_for_requests(&requests, 1).await;
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].body, payload.as_bytes());
+        assert_eq!(
+            requests[0].headers.get("x-event-id").map(String::as_str),
+            Some("pending-event")
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::utils::error::AppError;
+use sqlx::{AnyPool, Row};
+
+/// 记录每条链最后一个完整处理完毕的区块，用于重启后断点续扫
+#[derive(Clone)]
+pub struct CheckpointStore {
+    pool: AnyPool,
+}
+
+impl CheckpointStore {
+    pub async fn new(pool: AnyPool) -> Result<Self, AppError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS scan_checkpoints (
+                chain TEXT PRIMARY KEY,
+                last_block BIGINT NOT NULL,
+                updated_at BIGINT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(CheckpointStore { pool })
+    }
+
+    /// 读取某条链最后处理完成的区块号，没有记录时返回 None
+    pub async fn load(&self, chain: &str) -> Result<Option<u64>, AppError> {
+        let row = sqlx::query("SELECT last_block FROM scan_checkpoints WHERE chain = $1")
+            .bind(chain)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        match row {
+            Some(row) => {
+                let last_block: i64 = row
+                    .try_get("last_block")
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+                Ok(Some(last_block as u64))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn save(&self, chain: &str, block_num: u64) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO scan_checkpoints (chain, last_block, updated_at) VALUES ($1, $2, $3)
+             ON CONFLICT (chain) DO UPDATE SET last_block = excluded.last_block, updated_at = excluded.updated_at",
+        )
+        .bind(chain)
+        .bind(block_num as i64)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
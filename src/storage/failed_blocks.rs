@@ -0,0 +1,126 @@
+use crate::utils::error::AppError;
+use sqlx::{AnyPool, Row};
+
+/// 等待重新扫描的区块
+pub struct FailedBlock {
+    pub block_number: u64,
+    pub attempts: u32,
+}
+
+/// 多次扫描失败的区块（死信列表），由后台任务定期重新扫描，成功后删除
+#[derive(Clone)]
+pub struct FailedBlockStore {
+    pool: AnyPool,
+}
+
+impl FailedBlockStore {
+    pub async fn new(pool: AnyPool) -> Result<Self, AppError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS failed_blocks (
+                chain TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                attempts BIGINT NOT NULL,
+                next_attempt_at BIGINT NOT NULL,
+                last_error TEXT,
+                created_at BIGINT NOT NULL,
+                PRIMARY KEY (chain, block_number)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(FailedBlockStore { pool })
+    }
+
+    /// 加入死信列表，已存在时只更新错误信息
+    pub async fn add(
+        &self,
+        chain: &str,
+        block_number: u64,
+        attempts: u32,
+        next_attempt_at: i64,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        sqlx::query(
+            "INSERT INTO failed_blocks (chain, block_number, attempts, next_attempt_at, last_error, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (chain, block_number) DO UPDATE SET last_error = excluded.last_error",
+        )
+        .bind(chain)
+        .bind(block_number as i64)
+        .bind(attempts as i64)
+        .bind(next_attempt_at)
+        .bind(error)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 取出已到重试时间的区块，按区块号升序返回
+    pub async fn due(&self, chain: &str, limit: i64) -> Result<Vec<FailedBlock>, AppError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let rows = sqlx::query(
+            "SELECT block_number, attempts FROM failed_blocks
+             WHERE chain = $1 AND next_attempt_at <= $2
+             ORDER BY block_number LIMIT $3",
+        )
+        .bind(chain)
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                let block_number: i64 = row.try_get("block_number")?;
+                let attempts: i64 = row.try_get("attempts")?;
+                Ok(FailedBlock {
+                    block_number: block_number as u64,
+                    attempts: attempts as u32,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn delete(&self, chain: &str, block_number: u64) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM failed_blocks WHERE chain = $1 AND block_number = $2")
+            .bind(chain)
+            .bind(block_number as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn record_failure(
+        &self,
+        chain: &str,
+        block_number: u64,
+        attempts: u32,
+        next_attempt_at: i64,
+        error: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE failed_blocks SET attempts = $1, next_attempt_at = $2, last_error = $3
+             WHERE chain = $4 AND block_number = $5",
+        )
+        .bind(attempts as i64)
+        .bind(next_attempt_at)
+        .bind(error)
+        .bind(chain)
+        .bind(block_number as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
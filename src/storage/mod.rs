@@ -0,0 +1,32 @@
+pub mod checkpoint;
+pub mod failed_blocks;
+pub mod token_metadata;
+pub mod watchlist;
+pub mod webhook_queue;
+
+use crate::utils::config::DatabaseConfig;
+use crate::utils::error::AppError;
+use sqlx::any::{install_default_drivers, AnyPoolOptions};
+use sqlx::AnyPool;
+
+/// 根据 `database.url` 建立连接池，支持 sqlite:// 与 postgres:// 两种地址
+pub async fn connect(config: &DatabaseConfig) -> Result<AnyPool, AppError> {
+    install_default_drivers();
+
+    AnyPoolOptions::new()
+        .max_connections(config.max_connections)
+        .connect(&config.url)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+}
+
+/// 测试用的内存 SQLite 连接池；只有一个连接，所有查询看到同一个数据库
+#[cfg(test)]
+pub async fn connect_in_memory() -> AnyPool {
+    connect(&DatabaseConfig {
+        url: "sqlite::memory:".to_string(),
+        max_connections: 1,
+    })
+    .await
+    .unwrap()
+}
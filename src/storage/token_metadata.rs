@@ -0,0 +1,83 @@
+use crate::tokens::TokenMetadata;
+use crate::utils::error::AppError;
+use sqlx::{AnyPool, Row};
+
+/// 代币元数据的持久化缓存，避免每次重启都重新调用合约
+#[derive(Clone)]
+pub struct TokenMetadataStore {
+    pool: AnyPool,
+}
+
+impl TokenMetadataStore {
+    pub async fn new(pool: AnyPool) -> Result<Self, AppError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS token_metadata (
+                chain TEXT NOT NULL,
+                contract_address TEXT NOT NULL,
+                symbol TEXT,
+                name TEXT,
+                decimals BIGINT,
+                updated_at BIGINT NOT NULL,
+                PRIMARY KEY (chain, contract_address)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(TokenMetadataStore { pool })
+    }
+
+    pub async fn load(
+        &self,
+        chain: &str,
+        contract_address: &str,
+    ) -> Result<Option<TokenMetadata>, AppError> {
+        let row = sqlx::query(
+            "SELECT symbol, name, decimals FROM token_metadata
+             WHERE chain = $1 AND contract_address = $2",
+        )
+        .bind(chain)
+        .bind(contract_address)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        row.map(|row| {
+            let decimals: Option<i64> = row.try_get("decimals")?;
+            Ok(TokenMetadata {
+                symbol: row.try_get("symbol")?,
+                name: row.try_get("name")?,
+                decimals: decimals.map(|d| d as u8),
+            })
+        })
+        .transpose()
+        .map_err(|e: sqlx::Error| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn save(
+        &self,
+        chain: &str,
+        contract_address: &str,
+        metadata: &TokenMetadata,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO token_metadata (chain, contract_address, symbol, name, decimals, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (chain, contract_address) DO UPDATE SET
+                symbol = excluded.symbol, name = excluded.name,
+                decimals = excluded.decimals, updated_at = excluded.updated_at",
+        )
+        .bind(chain)
+        .bind(contract_address)
+        .bind(metadata.symbol.clone())
+        .bind(metadata.name.clone())
+        .bind(metadata.decimals.map(|d| d as i64))
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
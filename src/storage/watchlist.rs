@@ -0,0 +1,40 @@
+use crate::utils::error::AppError;
+use sqlx::{AnyPool, Row};
+
+/// 数据库中维护的监控地址，便于运营侧不重启服务即可增删地址
+#[derive(Clone)]
+pub struct WatchlistStore {
+    pool: AnyPool,
+}
+
+impl WatchlistStore {
+    pub async fn new(pool: AnyPool) -> Result<Self, AppError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS watch_addresses (
+                chain TEXT NOT NULL,
+                address TEXT NOT NULL,
+                PRIMARY KEY (chain, address)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(WatchlistStore { pool })
+    }
+
+    pub async fn load(&self, chain: &str) -> Result<Vec<String>, AppError> {
+        let rows = sqlx::query("SELECT address FROM watch_addresses WHERE chain = $1")
+            .bind(chain)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                row.try_get::<String, _>("address")
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,140 @@
+use crate::utils::error::AppError;
+use sqlx::{AnyPool, Row};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// 待投递的 webhook 事件
+pub struct QueuedEvent {
+    pub id: String,
+    pub payload: String,
+    pub attempts: u32,
+}
+
+/// 持久化的 webhook 投递队列，服务重启后未送达的事件会继续重试
+#[derive(Clone)]
+pub struct WebhookQueue {
+    pool: AnyPool,
+    /// 下一个入队序号；同一毫秒入队的事件（例如 revert 和随后的重新发出）也按序号保持顺序
+    next_seq: Arc<AtomicI64>,
+}
+
+impl WebhookQueue {
+    pub async fn new(pool: AnyPool) -> Result<Self, AppError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS webhook_queue (
+                id TEXT NOT NULL,
+                url TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL,
+                attempts BIGINT NOT NULL,
+                next_attempt_at BIGINT NOT NULL,
+                last_error TEXT,
+                created_at BIGINT NOT NULL,
+                seq BIGINT NOT NULL,
+                PRIMARY KEY (url, id)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // 重启后接着表中最大的序号继续，仍在排队的旧事件先于新事件投递
+        let max_seq: Option<i64> = sqlx::query("SELECT MAX(seq) AS max_seq FROM webhook_queue")
+            .fetch_one(&pool)
+            .await
+            .and_then(|row| row.try_get("max_seq"))
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(WebhookQueue {
+            pool,
+            next_seq: Arc::new(AtomicI64::new(max_seq.map_or(0, |seq| seq + 1))),
+        })
+    }
+
+    /// 入队，同一事件重复入队时忽略
+    pub async fn enqueue(&self, url: &str, id: &str, payload: &str) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        sqlx::query(
+            "INSERT INTO webhook_queue (id, url, payload, status, attempts, next_attempt_at, created_at, seq)
+             VALUES ($1, $2, $3, 'pending', 0, $4, $4, $5)
+             ON CONFLICT (url, id) DO NOTHING",
+        )
+        .bind(id)
+        .bind(url)
+        .bind(payload)
+        .bind(now)
+        .bind(seq)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 取出已到重试时间的事件，按入队顺序返回
+    pub async fn due(&self, url: &str, limit: i64) -> Result<Vec<QueuedEvent>, AppError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let rows = sqlx::query(
+            "SELECT id, payload, attempts FROM webhook_queue
+             WHERE url = $1 AND status = 'pending' AND next_attempt_at <= $2
+             ORDER BY seq LIMIT $3",
+        )
+        .bind(url)
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                let attempts: i64 = row.try_get("attempts")?;
+                Ok(QueuedEvent {
+                    id: row.try_get("id")?,
+                    payload: row.try_get("payload")?,
+                    attempts: attempts as u32,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn delete(&self, url: &str, id: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM webhook_queue WHERE url = $1 AND id = $2")
+            .bind(url)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 记录一次失败的投递；dead 为 true 时不再重试，保留在表中供人工处理
+    pub async fn record_failure(
+        &self,
+        url: &str,
+        id: &str,
+        attempts: u32,
+        next_attempt_at: i64,
+        dead: bool,
+        error: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE webhook_queue SET attempts = $1, next_attempt_at = $2, status = $3, last_error = $4
+             WHERE url = $5 AND id = $6",
+        )
+        .bind(attempts as i64)
+        .bind(next_attempt_at)
+        .bind(if dead { "dead" } else { "pending" })
+        .bind(error)
+        .bind(url)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
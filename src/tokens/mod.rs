@@ -0,0 +1,243 @@
+use crate::scanners::types::{ScannedTransaction, TokenStandard};
+use crate::storage::token_metadata::TokenMetadataStore;
+use crate::utils::abi::{data_word, decode_string, decode_uint256};
+use crate::utils::error::AppError;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use tracing::warn;
+
+/// 无参数的 view 方法，同时给出签名和 4 字节 selector，各链按需使用
+pub struct ViewMethod {
+    pub signature: &'static str,
+    pub selector: &'static str,
+}
+
+pub const SYMBOL: ViewMethod = ViewMethod {
+    signature: "symbol()",
+    selector: "95d89b41",
+};
+
+pub const NAME: ViewMethod = ViewMethod {
+    signature: "name()",
+    selector: "06fdde03",
+};
+
+pub const DECIMALS: ViewMethod = ViewMethod {
+    signature: "decimals()",
+    selector: "313ce567",
+};
+
+/// 代币元数据；合约没有实现对应方法时字段为 None
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadata {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+/// 由各链扫描器实现的只读合约调用
+#[async_trait]
+pub trait TokenMetadataSource: Send + Sync {
+    /// 调用无参数的 view 方法，返回十六进制编码的结果
+    async fn call_view(
+        &self,
+        contract_address: &str,
+        method: &ViewMethod,
+    ) -> Result<String, AppError>;
+}
+
+/// 单条链的代币元数据注册表，先查内存，再查数据库，最后调用合约
+pub struct TokenRegistry {
+    chain: String,
+    store: TokenMetadataStore,
+    cache: RwLock<HashMap<String, TokenMetadata>>,
+}
+
+impl TokenRegistry {
+    pub fn new(chain: &str, store: TokenMetadataStore) -> Self {
+        TokenRegistry {
+            chain: chain.to_string(),
+            store,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 为交易中的每笔代币转账补充 symbol、decimals 和按精度换算后的 amount
+    pub async fn annotate(
+        &self,
+        transactions: &mut [ScannedTransaction],
+        source: &dyn TokenMetadataSource,
+    ) {
+        let contracts: HashSet<String> = transactions
+            .iter()
+            .flat_map(|tx| tx.token_transfers.iter())
+            // TRC-10 不是合约，没有可调用的 view 方法
+            .filter(|transfer| transfer.standard != TokenStandard::Trc10)
+            .map(|transfer| transfer.contract_address.clone())
+            .collect();
+
+        let mut resolved = HashMap::new();
+        for contract in contracts {
+            match self.resolve(&contract, source).await {
+                Ok(metadata) => {
+                    resolved.insert(contract, metadata);
+                }
+                Err(e) => warn!(
+                    "Failed to resolve {} token metadata for {}: {}",
+                    self.chain, contract, e
+                ),
+            }
+        }
+
+        for transfer in transactions
+            .iter_mut()
+            .flat_map(|tx| tx.token_transfers.iter_mut())
+        {
+            if let Some(metadata) = resolved.get(&transfer.contract_address) {
+                transfer.symbol = metadata.symbol.clone();
+                transfer.decimals = metadata.decimals;
+                transfer.amount = metadata
+                    .decimals
+                    .filter(|_| transfer.token_id.is_none())
+                    .map(|decimals| format_units(&transfer.value, decimals));
+            }
+        }
+    }
+
+    pub async fn resolve(
+        &self,
+        contract_address: &str,
+        source: &dyn TokenMetadataSource,
+    ) -> Result<TokenMetadata, AppError> {
+        if let Some(metadata) = self.cache.read().unwrap().get(contract_address) {
+            return Ok(metadata.clone());
+        }
+
+        let metadata = match self.store.load(&self.chain, contract_address).await? {
+            Some(metadata) => metadata,
+            None => {
+                let metadata = fetch_metadata(contract_address, source).await?;
+                self.store
+                    .save(&self.chain, contract_address, &metadata)
+                    .await?;
+                metadata
+            }
+        };
+
+        self.cache
+            .write()
+            .unwrap()
+            .insert(contract_address.to_string(), metadata.clone());
+        Ok(metadata)
+    }
+}
+
+async fn fetch_metadata(
+    contract_address: &str,
+    source: &dyn TokenMetadataSource,
+) -> Result<TokenMetadata, AppError> {
+    let decimals = call_optional(source, contract_address, &DECIMALS)
+        .await?
+        .and_then(|result| data_word(&result, 0).and_then(decode_uint256).ok())
+        .and_then(|d| u8::try_from(d).ok());
+    let symbol = call_optional(source, contract_address, &SYMBOL)
+        .await?
+        .and_then(|result| decode_string(&result).ok());
+    let name = call_optional(source, contract_address, &NAME)
+        .await?
+        .and_then(|result| decode_string(&result).ok());
+
+    Ok(TokenMetadata {
+        symbol,
+        name,
+        decimals,
+    })
+}
+
+/// 合约未实现该方法（调用被回滚或返回空）时返回 None；其他错误（网络、限流、节点错误）
+/// 向上返回，不会作为“没有该方法”缓存下来
+async fn call_optional(
+    source: &dyn TokenMetadataSource,
+    contract_address: &str,
+    method: &ViewMethod,
+) -> Result<Option<String>, AppError> {
+    match source.call_view(contract_address, method).await {
+        Ok(result) => {
+            let result = result.trim_start_matches("0x").to_string();
+            Ok((!result.is_empty()).then_some(result))
+        }
+        Err(AppError::ContractReverted(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// 把最小单位的整数字符串按 decimals 换算为十进制小数，去掉末尾的 0
+pub fn format_units(raw: &str, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return raw.to_string();
+    }
+
+    let padded = format!("{:0>width$}", raw, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// decimals 返回 18，symbol 被回滚；failing 时所有调用都返回网络错误
+    struct FakeToken {
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl TokenMetadataSource for FakeToken {
+        async fn call_view(
+            &self,
+            _contract_address: &str,
+            method: &ViewMethod,
+        ) -> Result<String, AppError> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(AppError::NetworkError("connection reset".to_string()));
+            }
+            match method.selector {
+                "313ce567" => Ok(format!("0x{:064x}", 18)),
+                _ => Err(AppError::ContractReverted("execution reverted".to_string())),
+            }
+        }
+    }
+
+    async fn test_registry() -> (TokenRegistry, TokenMetadataStore) {
+        let pool = crate::storage::connect_in_memory().await;
+        let store = TokenMetadataStore::new(pool).await.unwrap();
+        (TokenRegistry::new("test", store.clone()), store)
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_not_cached_as_missing_methods() {
+        let (registry, store) = test_registry().await;
+        let token = FakeToken {
+            failing: AtomicBool::new(true),
+        };
+
+        assert!(registry.resolve("0xtoken", &token).await.is_err());
+        assert!(store.load("test", "0xtoken").await.unwrap().is_none());
+
+        token.failing.store(false, Ordering::SeqCst);
+        let metadata = registry.resolve("0xtoken", &token).await.unwrap();
+        assert_eq!(metadata.decimals, Some(18));
+        // 被回滚的方法才记为 None
+        assert_eq!(metadata.symbol, None);
+        let stored = store.load("test", "0xtoken").await.unwrap().unwrap();
+        assert_eq!(stored.decimals, Some(18));
+    }
+}
@@ -0,0 +1,330 @@
+use num_bigint::BigUint;
+use num_traits::Num;
+use serde::{Deserialize, Serialize};
+
+use super::error::AppError;
+
+/// keccak256("Transfer(address,address,uint256)")，ERC-20 与 ERC-721 共用
+pub const TRANSFER_EVENT_TOPIC: &str =
+    "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// transfer(address,uint256)、transferFrom(address,address,uint256)、approve(address,uint256) 的方法选择器
+const TRANSFER_SELECTOR: &str = "a9059cbb";
+const TRANSFER_FROM_SELECTOR: &str = "23b872dd";
+const APPROVE_SELECTOR: &str = "095ea7b3";
+
+/// 去掉 0x 前缀并校验是否为一个 32 字节的 ABI 字（64 个十六进制字符）
+fn abi_word(word: &str) -> Result<&str, AppError> {
+    let word = word.trim_start_matches("0x");
+    if word.len() != 64 || !word.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::ParseError(format!("Invalid ABI word: {}", word)));
+    }
+    Ok(word)
+}
+
+/// 从 indexed address 参数中取出地址，返回 40 个小写十六进制字符（不带前缀）
+pub fn decode_address_word(word: &str) -> Result<String, AppError> {
+    let word = abi_word(word)?;
+    if !word[..24].chars().all(|c| c == '0') {
+        return Err(AppError::ParseError(format!(
+            "Address word has non-zero padding: {}",
+            word
+        )));
+    }
+    Ok(word[24..].to_lowercase())
+}
+
+/// 解析完整的 uint256，不会因为超过 u128 而截断
+pub fn decode_uint256(word: &str) -> Result<BigUint, AppError> {
+    let word = abi_word(word)?;
+    BigUint::from_str_radix(word, 16).map_err(|e| AppError::ParseError(e.to_string()))
+}
+
+/// 取出 data 中第 index 个 32 字节的字
+pub fn data_word(data: &str, index: usize) -> Result<&str, AppError> {
+    let data = data.trim_start_matches("0x");
+    let too_short = || AppError::ParseError(format!("ABI data too short for word {}", index));
+    let start = index.checked_mul(64).ok_or_else(too_short)?;
+    let end = start.checked_add(64).ok_or_else(too_short)?;
+    data.get(start..end).ok_or_else(too_short)
+}
+
+/// 解码后的 Transfer 事件，地址为 40 个十六进制字符
+pub struct DecodedTransfer {
+    pub from: String,
+    pub to: String,
+    pub value: BigUint,
+    /// ERC-721 的 tokenId，ERC-20 为 None
+    pub token_id: Option<BigUint>,
+}
+
+/// 解码 Transfer 事件日志，不是 Transfer 事件时返回 None。
+///
+/// ERC-20 有 3 个 topic，数量在 data 中；ERC-721 的 tokenId 同样是 indexed 参数，
+/// 位于 topics[3]，data 为空。
+pub fn decode_transfer_log(
+    topics: &[&str],
+    data: &str,
+) -> Result<Option<DecodedTransfer>, AppError> {
+    if topics.first().map(|t| t.trim_start_matches("0x")) != Some(TRANSFER_EVENT_TOPIC) {
+        return Ok(None);
+    }
+
+    let (value, token_id) = match topics.len() {
+        3 => (decode_uint256(data_word(data, 0)?)?, None),
+        4 => (BigUint::from(1u8), Some(decode_uint256(topics[3])?)),
+        n => {
+            return Err(AppError::ParseError(format!(
+                "Unexpected Transfer topic count: {}",
+                n
+            )))
+        }
+    };
+
+    Ok(Some(DecodedTransfer {
+        from: decode_address_word(topics[1])?,
+        to: decode_address_word(topics[2])?,
+        value,
+        token_id,
+    }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenCallMethod {
+    Transfer,
+    TransferFrom,
+    Approve,
+}
+
+/// 解码后的代币方法调用，地址为 40 个十六进制字符
+pub struct DecodedTokenCall {
+    pub method: TokenCallMethod,
+    /// transferFrom 的转出地址，其他方法为 None（即调用者）
+    pub from: Option<String>,
+    /// 收款地址，approve 为被授权地址
+    pub to: String,
+    pub value: BigUint,
+}
+
+/// 解码 transfer / transferFrom / approve 的调用数据，其他方法返回 None
+pub fn decode_token_call(data: &str) -> Result<Option<DecodedTokenCall>, AppError> {
+    let data = data.trim_start_matches("0x");
+    if !data.len().is_multiple_of(2) {
+        return Err(AppError::ParseError(format!(
+            "Call data has odd length: {}",
+            data.len()
+        )));
+    }
+    let (selector, args) = match (data.get(..8), data.get(8..)) {
+        (Some(selector), Some(args)) => (selector.to_lowercase(), args),
+        _ => return Ok(None),
+    };
+
+    let call = match selector.as_str() {
+        TRANSFER_SELECTOR | APPROVE_SELECTOR => DecodedTokenCall {
+            method: if selector == TRANSFER_SELECTOR {
+                TokenCallMethod::Transfer
+            } else {
+                TokenCallMethod::Approve
+            },
+            from: None,
+            to: decode_address_word(data_word(args, 0)?)?,
+            value: decode_uint256(data_word(args, 1)?)?,
+        },
+        TRANSFER_FROM_SELECTOR => DecodedTokenCall {
+            method: TokenCallMethod::TransferFrom,
+            from: Some(decode_address_word(data_word(args, 0)?)?),
+            to: decode_address_word(data_word(args, 1)?)?,
+            value: decode_uint256(data_word(args, 2)?)?,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(call))
+}
+
+/// 解码 string 类型的返回值；部分老合约（如 MKR）返回 bytes32，也一并兼容
+pub fn decode_string(data: &str) -> Result<String, AppError> {
+    let hex_data = data.trim_start_matches("0x");
+
+    let bytes = if hex_data.len() == 64 {
+        let raw = hex::decode(hex_data).map_err(|e| AppError::ParseError(e.to_string()))?;
+        raw.into_iter().take_while(|b| *b != 0).collect()
+    } else {
+        // offset 和 length 来自合约返回值，不可信，全部做溢出和越界检查
+        let offset = decode_usize(data_word(hex_data, 0)?)?;
+        if offset % 32 != 0 || offset.saturating_mul(2) >= hex_data.len() {
+            return Err(AppError::ParseError(format!(
+                "Invalid string offset: {}",
+                offset
+            )));
+        }
+        let length = decode_usize(data_word(hex_data, offset / 32)?)?;
+        let too_short = || AppError::ParseError("String data too short".to_string());
+        let start = offset
+            .checked_mul(2)
+            .and_then(|start| start.checked_add(64))
+            .ok_or_else(too_short)?;
+        let end = length
+            .checked_mul(2)
+            .and_then(|length| start.checked_add(length))
+            .filter(|end| *end <= hex_data.len())
+            .ok_or_else(too_short)?;
+        let content = hex_data.get(start..end).ok_or_else(too_short)?;
+        hex::decode(content).map_err(|e| AppError::ParseError(e.to_string()))?
+    };
+
+    String::from_utf8(bytes).map_err(|e| AppError::ParseError(e.to_string()))
+}
+
+fn decode_usize(word: &str) -> Result<usize, AppError> {
+    let value = decode_uint256(word)?;
+    usize::try_from(value).map_err(|e| AppError::ParseError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FROM: &str = "0x000000000000000000000000a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+    const TO: &str = "0x0000000000000000000000004e83362442b8d1bec281594cea3050c8eb01311c";
+
+    fn word(value: u64) -> String {
+        format!("{:064x}", value)
+    }
+
+    #[test]
+    fn decodes_erc20_transfer_with_value_in_data() {
+        let topics = [TRANSFER_EVENT_TOPIC, FROM, TO];
+        let transfer = decode_transfer_log(&topics, &format!("0x{}", word(5_000_000)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(transfer.from, "a614f803b6fd780986a42c78ec9c7f77e6ded13c");
+        assert_eq!(transfer.to, "4e83362442b8d1bec281594cea3050c8eb01311c");
+        assert_eq!(transfer.value, BigUint::from(5_000_000u64));
+        assert!(transfer.token_id.is_none());
+    }
+
+    #[test]
+    fn decodes_erc721_transfer_with_token_id_topic() {
+        let token_id = format!("0x{}", word(42));
+        let topics = [TRANSFER_EVENT_TOPIC, FROM, TO, token_id.as_str()];
+        let transfer = decode_transfer_log(&topics, "0x").unwrap().unwrap();
+        assert_eq!(transfer.value, BigUint::from(1u8));
+        assert_eq!(transfer.token_id, Some(BigUint::from(42u8)));
+    }
+
+    #[test]
+    fn ignores_other_events() {
+        let approval = "8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+        assert!(decode_transfer_log(&[approval, FROM, TO], &word(1))
+            .unwrap()
+            .is_none());
+        assert!(decode_transfer_log(&[], "").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_transfer_logs() {
+        // 缺少 to
+        assert!(decode_transfer_log(&[TRANSFER_EVENT_TOPIC, FROM], &word(1)).is_err());
+        // ERC-20 的 data 为空
+        assert!(decode_transfer_log(&[TRANSFER_EVENT_TOPIC, FROM, TO], "0x").is_err());
+        // topic 长度不对或不是十六进制
+        assert!(decode_transfer_log(&[TRANSFER_EVENT_TOPIC, "0x1234", TO], &word(1)).is_err());
+        let not_hex = "zz".repeat(32);
+        assert!(decode_transfer_log(&[TRANSFER_EVENT_TOPIC, FROM, &not_hex], &word(1)).is_err());
+    }
+
+    #[test]
+    fn rejects_address_word_with_non_zero_padding() {
+        let dirty = "0x000000000000000000000001a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+        assert!(decode_address_word(dirty).is_err());
+        assert_eq!(
+            decode_address_word(FROM).unwrap(),
+            "a614f803b6fd780986a42c78ec9c7f77e6ded13c"
+        );
+    }
+
+    #[test]
+    fn decodes_abi_string_and_bytes32() {
+        let symbol = format!("{}{}{:0<64}", word(32), word(4), hex::encode("USDT"));
+        assert_eq!(decode_string(&format!("0x{}", symbol)).unwrap(), "USDT");
+        let bytes32 = format!("{:0<64}", hex::encode("MKR"));
+        assert_eq!(decode_string(&bytes32).unwrap(), "MKR");
+    }
+
+    #[test]
+    fn rejects_hostile_string_offsets_and_lengths() {
+        let max = "f".repeat(64);
+        // offset 超出数据或在乘 2 时溢出
+        assert!(decode_string(&format!("{}{}", word(u64::MAX - 31), word(4))).is_err());
+        assert!(decode_string(&format!("{}{}", max, word(4))).is_err());
+        assert!(decode_string(&format!("{}{}", word(4096), word(4))).is_err());
+        // length 超出数据或在乘 2 时溢出
+        assert!(decode_string(&format!("{}{}", word(32), word(u64::MAX))).is_err());
+        assert!(decode_string(&format!("{}{}", word(32), word(100))).is_err());
+        assert!(data_word(&max, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn decodes_token_call_selectors() {
+        let to = &FROM[2..];
+        let owner = &TO[2..];
+
+        let transfer = decode_token_call(&format!("0xa9059cbb{}{}", to, word(1_000_000)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(transfer.method, TokenCallMethod::Transfer);
+        assert_eq!(transfer.from, None);
+        assert_eq!(transfer.to, "a614f803b6fd780986a42c78ec9c7f77e6ded13c");
+        assert_eq!(transfer.value, BigUint::from(1_000_000u64));
+
+        let transfer_from = decode_token_call(&format!("23b872dd{}{}{}", owner, to, word(7)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(transfer_from.method, TokenCallMethod::TransferFrom);
+        assert_eq!(
+            transfer_from.from.as_deref(),
+            Some("4e83362442b8d1bec281594cea3050c8eb01311c")
+        );
+        assert_eq!(transfer_from.to, "a614f803b6fd780986a42c78ec9c7f77e6ded13c");
+        assert_eq!(transfer_from.value, BigUint::from(7u8));
+
+        let approve = decode_token_call(&format!("095EA7B3{}{}", to, "f".repeat(64)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(approve.method, TokenCallMethod::Approve);
+        assert_eq!(approve.value.bits(), 256);
+    }
+
+    #[test]
+    fn ignores_other_methods_and_rejects_short_call_data() {
+        // balanceOf(address) 和不足 4 字节的数据不是代币调用
+        let to = &FROM[2..];
+        assert!(decode_token_call(&format!("70a08231{}", to))
+            .unwrap()
+            .is_none());
+        assert!(decode_token_call("a905").unwrap().is_none());
+        assert!(decode_token_call("").unwrap().is_none());
+
+        // 选择器正确但参数不完整，或长度为奇数
+        assert!(decode_token_call(&format!("a9059cbb{}", to)).is_err());
+        assert!(decode_token_call(&format!("a9059cbb{}{}", to, &word(1)[1..])).is_err());
+        assert!(decode_token_call(&format!("a9059cbb{}{}0", to, word(1))).is_err());
+        assert!(decode_token_call(&format!("23b872dd{}{}", to, to)).is_err());
+    }
+
+    #[test]
+    fn decodes_uint256_above_u64() {
+        let max = "f".repeat(64);
+        assert_eq!(
+            decode_uint256(&max).unwrap().to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        let above_u64 = format!("{:0>64}", "10000000000000000");
+        assert_eq!(
+            decode_uint256(&above_u64).unwrap(),
+            BigUint::from(u64::MAX) + BigUint::from(1u8)
+        );
+    }
+}
@@ -1,35 +1,472 @@
 use config::{Config, ConfigError, File};
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Debug, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
 }
 
+fn default_max_connections() -> u32 {
+    5
+}
+
+/// 一个扫描器实例的配置，kind 决定由哪种扫描器处理，同一种扫描器可以配置多个实例
 #[derive(Debug, Deserialize)]
-pub struct TronConfig {
+pub struct ChainConfig {
+    /// 扫描器类型，例如 tron、evm
+    pub kind: String,
+    /// 链名称，同时作为输出中的 chain 字段和断点、监控地址、代币元数据的键，必须唯一
+    pub name: String,
+    #[serde(default = "default_enable")]
     pub enable: bool,
-    pub api_url: String,
+    /// 只有一个端点时的简写，等同于 endpoints = [{ url = api_url }]
+    #[serde(default)]
+    pub api_url: Option<String>,
+    #[serde(default)]
+    pub endpoints: Vec<EndpointConfig>,
+    #[serde(default)]
+    pub rpc: RpcConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
     pub start_block: u64,
+    /// 轮询间隔，未配置时使用 scheduler.interval_seconds
+    #[serde(default)]
+    pub interval_seconds: Option<u64>,
+    /// 区块距离链头至少这么多个块之后才处理
+    #[serde(default)]
+    pub confirmations: u64,
+    #[serde(default)]
+    pub watchlist: WatchlistConfig,
+    /// 扫描器类型特有的参数，由对应的扫描器通过 options() 解析
+    #[serde(default)]
+    pub options: serde_json::Value,
+}
+
+impl ChainConfig {
+    /// api_url 和 endpoints 合并后的全部端点，api_url 排在最前
+    pub fn all_endpoints(&self) -> Vec<EndpointConfig> {
+        self.api_url
+            .iter()
+            .map(|url| EndpointConfig {
+                url: url.clone(),
+                weight: default_endpoint_weight(),
+            })
+            .chain(self.endpoints.iter().cloned())
+            .collect()
+    }
+
+    pub fn options<T: DeserializeOwned>(&self) -> Result<T, ConfigError> {
+        let options = match &self.options {
+            serde_json::Value::Null => serde_json::json!({}),
+            options => options.clone(),
+        };
+        serde_json::from_value(options).map_err(|e| {
+            ConfigError::Message(format!("Invalid options for chain {}: {}", self.name, e))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EndpointConfig {
+    pub url: String,
+    /// load_balancing = "weighted" 时的相对权重
+    #[serde(default = "default_endpoint_weight")]
+    pub weight: u32,
+}
+
+fn default_endpoint_weight() -> u32 {
+    1
+}
+
+/// 扫描器访问节点时使用的 HTTP 客户端配置
+#[derive(Debug, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default = "default_http_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    /// 单个请求从发送到读完响应的总超时
+    #[serde(default = "default_http_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// 例如 http://127.0.0.1:7890 或 socks5://127.0.0.1:1080
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// 附加到每个请求的请求头，例如 TRON-PRO-API-KEY
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 额外信任的 PEM 格式 CA 证书路径
+    #[serde(default)]
+    pub ca_certificate: Option<String>,
+    /// 跳过证书校验，只应在测试环境使用
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    /// 每个主机保留的最大空闲连接数
+    #[serde(default = "default_http_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+    #[serde(default = "default_http_pool_idle_timeout_seconds")]
+    pub pool_idle_timeout_seconds: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            connect_timeout_seconds: default_http_connect_timeout_seconds(),
+            timeout_seconds: default_http_timeout_seconds(),
+            proxy: None,
+            headers: HashMap::new(),
+            ca_certificate: None,
+            accept_invalid_certs: false,
+            pool_max_idle_per_host: default_http_pool_max_idle_per_host(),
+            pool_idle_timeout_seconds: default_http_pool_idle_timeout_seconds(),
+        }
+    }
+}
+
+fn default_http_connect_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_http_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_http_pool_max_idle_per_host() -> usize {
+    32
+}
+
+fn default_http_pool_idle_timeout_seconds() -> u64 {
+    90
+}
+
+/// 多个 RPC 端点之间的负载均衡和健康检查配置
+#[derive(Debug, Deserialize)]
+pub struct RpcConfig {
+    #[serde(default)]
+    pub load_balancing: LoadBalancing,
+    /// 端点失败后暂停使用的时间
+    #[serde(default = "default_rpc_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+    /// 获取最新区块时同时询问所有端点并相互核对
+    #[serde(default)]
+    pub cross_check_latest: bool,
+    /// 交叉核对时允许端点落后于最高区块的数量
+    #[serde(default = "default_rpc_max_block_lag")]
+    pub max_block_lag: u64,
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        RpcConfig {
+            load_balancing: LoadBalancing::default(),
+            cooldown_seconds: default_rpc_cooldown_seconds(),
+            cross_check_latest: false,
+            max_block_lag: default_rpc_max_block_lag(),
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+fn default_rpc_cooldown_seconds() -> u64 {
+    30
+}
+
+fn default_rpc_max_block_lag() -> u64 {
+    5
 }
 
+/// RPC 请求失败后的重试策略，所有端点都失败后才会重试
 #[derive(Debug, Deserialize)]
-pub struct BscConfig {
+pub struct RetryConfig {
+    /// 包括第一次请求在内的最大尝试次数
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// 退避时间随机浮动的比例，0.2 表示 ±20%
+    #[serde(default = "default_retry_jitter")]
+    pub jitter: f64,
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<RetryableError>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            jitter: default_retry_jitter(),
+            retry_on: default_retry_on(),
+        }
+    }
+}
+
+fn default_retry_max_attempts() -> u32 {
+    3
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    10_000
+}
+
+fn default_retry_jitter() -> f64 {
+    0.2
+}
+
+fn default_retry_on() -> Vec<RetryableError> {
+    vec![
+        RetryableError::Network,
+        RetryableError::ServerError,
+        RetryableError::RateLimited,
+    ]
+}
+
+/// 可以配置为重试的错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryableError {
+    /// 连接失败、超时等网络错误
+    Network,
+    /// HTTP 5xx
+    ServerError,
+    /// HTTP 429
+    RateLimited,
+    /// 节点返回的 JSON-RPC / API 错误
+    Api,
+    /// 响应不是合法的 JSON
+    InvalidResponse,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalancing {
+    /// 按配置顺序使用第一个健康的端点，其余作为备用
+    #[default]
+    Failover,
+    /// 依次轮流使用各个端点
+    RoundRobin,
+    /// 按 weight 比例轮流使用各个端点
+    Weighted,
+}
+
+/// kind = "tron" 的扫描器参数
+#[derive(Debug, Default, Deserialize)]
+pub struct TronOptions {
+    /// TronGrid 的 TRON-PRO-API-KEY，多个 key 轮流使用
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// 每个 key 每秒的请求数；没有 key 时限制匿名请求，未配置则不限制匿名请求
+    #[serde(default)]
+    pub requests_per_second: Option<f64>,
+    /// 令牌桶容量，默认等于 requests_per_second
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+impl TronOptions {
+    /// requests_per_second 必须是正的有限值，否则令牌桶的等待时间无法表示
+    pub fn validate(&self, chain: &str) -> Result<(), ConfigError> {
+        match self.requests_per_second {
+            Some(rate) if !(rate.is_finite() && rate > 0.0) => Err(ConfigError::Message(format!(
+                "Invalid options for chain {}: requests_per_second must be a positive number, got {}",
+                chain, rate
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// kind = "evm" 的扫描器参数
+#[derive(Debug, Deserialize)]
+pub struct EvmOptions {
+    /// 启动时与节点 eth_chainId 的返回值核对
+    pub chain_id: u64,
+    /// 原生币符号，例如 BNB、ETH
+    pub native_symbol: String,
+    /// 为检测链重组而保留的最近区块数量
+    #[serde(default = "default_max_reorg_depth")]
+    pub max_reorg_depth: u64,
+    /// 拉取区块回执的方式，默认自动探测节点支持的方法
+    #[serde(default)]
+    pub block_fetch_mode: BlockFetchMode,
+    /// 单个 JSON-RPC 批量请求中的最大调用数，设为 1 时不使用批量请求
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_enable() -> bool {
+    true
+}
+
+fn default_max_reorg_depth() -> u64 {
+    64
+}
+
+/// EVM 节点拉取区块回执的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockFetchMode {
+    /// 依次尝试 block_receipts、quicknode、transaction_receipts
+    #[default]
+    Auto,
+    /// QuickNode 专有的 qn_getBlockWithReceipts
+    #[serde(rename = "quicknode")]
+    QuickNode,
+    /// eth_getBlockByNumber + eth_getBlockReceipts
+    BlockReceipts,
+    /// eth_getBlockByNumber + 逐笔 eth_getTransactionReceipt
+    TransactionReceipts,
+}
+
+/// 监控地址配置，开启后只输出与这些地址相关的交易
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchlistConfig {
+    #[serde(default)]
     pub enable: bool,
-    pub api_url: String,
-    pub start_block: u64,
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// 每行一个地址的文件，# 开头为注释
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 从文件和数据库重新加载地址的间隔
+    #[serde(default = "default_watchlist_reload_seconds")]
+    pub reload_seconds: u64,
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        WatchlistConfig {
+            enable: false,
+            addresses: Vec::new(),
+            file: None,
+            reload_seconds: default_watchlist_reload_seconds(),
+        }
+    }
+}
+
+fn default_watchlist_reload_seconds() -> u64 {
+    60
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SchedulerConfig {
     pub interval_seconds: u64,
+    /// 追块时同时拉取的区块数量
+    #[serde(default = "default_catch_up_concurrency")]
+    pub catch_up_concurrency: usize,
+    /// 节点没有新区块时轮询间隔逐步翻倍的上限
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+    /// 同一个区块原地重试的次数，超过后写入死信列表
+    #[serde(default = "default_max_block_attempts")]
+    pub max_block_attempts: u32,
+    /// 后台重新扫描死信列表中区块的间隔
+    #[serde(default = "default_failed_block_retry_seconds")]
+    pub failed_block_retry_seconds: u64,
+}
+
+fn default_catch_up_concurrency() -> usize {
+    4
+}
+
+fn default_max_backoff_seconds() -> u64 {
+    60
+}
+
+fn default_max_block_attempts() -> u32 {
+    5
+}
+
+fn default_failed_block_retry_seconds() -> u64 {
+    60
+}
+
+/// 输出目标配置，通过 type 字段区分
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkConfig {
+    Stdout,
+    File(FileSinkConfig),
+    Database(DatabaseSinkConfig),
+    Webhook(WebhookSinkConfig),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileSinkConfig {
+    pub path: String,
+    pub file_name: String,
+    /// minutely / hourly / daily / never
+    #[serde(default = "default_file_sink_rotation")]
+    pub rotation: String,
+}
+
+fn default_file_sink_rotation() -> String {
+    "daily".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DatabaseSinkConfig {
+    #[serde(default = "default_database_sink_table")]
+    pub table: String,
+}
+
+fn default_database_sink_table() -> String {
+    "scanned_transactions".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookSinkConfig {
+    pub url: String,
+    /// 设置后用 HMAC-SHA256 对请求体签名，放在 X-Signature 头中
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// 超过该次数仍失败的事件标记为 dead，不再重试
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_webhook_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_webhook_max_delay_ms")]
+    pub max_delay_ms: u64,
+    #[serde(default = "default_webhook_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    10
+}
+
+fn default_webhook_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_webhook_max_delay_ms() -> u64 {
+    300_000
+}
+
+fn default_webhook_timeout_seconds() -> u64 {
+    10
 }
 
 #[derive(Debug, Deserialize)]
 pub struct LogConfig {
     pub level: String,
     pub output: String,
+    #[allow(dead_code)]
     pub format: String,
     pub file_path: String,
     pub file_name: String,
@@ -38,10 +475,17 @@ pub struct LogConfig {
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
-    pub tron: TronConfig,
-    pub bsc: BscConfig,
+    #[serde(default)]
+    pub chains: Vec<ChainConfig>,
     pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
     pub log: LogConfig,
+    /// 旧版本的顶层 [tron] / [bsc] 配置，只用于提示改成 [[chains]]
+    #[serde(default)]
+    tron: Option<IgnoredAny>,
+    #[serde(default)]
+    bsc: Option<IgnoredAny>,
 }
 
 impl AppConfig {
@@ -53,6 +497,130 @@ impl AppConfig {
             .add_source(File::with_name(&format!("config/{}", run_mode)).required(false))
             .build()?;
 
-        s.try_deserialize()
+        let config: AppConfig = s.try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 旧的顶层链配置会被静默忽略，没有启用的链时进程只会空等，两者都在启动时报错
+    fn validate(&self) -> Result<(), ConfigError> {
+        for (key, present) in [("tron", self.tron.is_some()), ("bsc", self.bsc.is_some())] {
+            if present {
+                return Err(ConfigError::Message(format!(
+                    "The top-level [{}] section is no longer supported; \
+                     configure the chain as a [[chains]] entry with a kind and a name",
+                    key
+                )));
+            }
+        }
+        if !self.chains.iter().any(|chain| chain.enable) {
+            return Err(ConfigError::Message(
+                "No chain is enabled; configure at least one [[chains]] entry".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tron_options(requests_per_second: Option<f64>) -> TronOptions {
+        TronOptions {
+            requests_per_second,
+            ..TronOptions::default()
+        }
+    }
+
+    fn app_config(chains: &str) -> Result<AppConfig, ConfigError> {
+        let toml = format!(
+            r#"
+            [database]
+            url = "sqlite::memory:"
+
+            [scheduler]
+            interval_seconds = 3
+
+            [log]
+            level = "info"
+            output = "stdout"
+            format = "text"
+            file_path = "logs"
+            file_name = "log"
+
+            {}
+            "#,
+            chains
+        );
+        let config: AppConfig = Config::builder()
+            .add_source(File::from_str(&toml, config::FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[test]
+    fn accepts_enabled_chains() {
+        let config = app_config(
+            r#"
+            [[chains]]
+            kind = "tron"
+            name = "TRON"
+            api_url = "https://api.trongrid.io"
+            start_block = 0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.chains.len(), 1);
+    }
+
+    #[test]
+    fn rejects_configs_without_enabled_chains() {
+        assert!(app_config("").is_err());
+        let disabled = app_config(
+            r#"
+            [[chains]]
+            kind = "tron"
+            name = "TRON"
+            enable = false
+            api_url = "https://api.trongrid.io"
+            start_block = 0
+            "#,
+        );
+        assert!(disabled.is_err());
+    }
+
+    #[test]
+    fn rejects_legacy_top_level_chain_sections() {
+        for legacy in ["tron", "bsc"] {
+            let error = app_config(&format!(
+                r#"
+                [{}]
+                enable = true
+                api_url = "https://example.com"
+                start_block = 0
+
+                [[chains]]
+                kind = "tron"
+                name = "TRON"
+                api_url = "https://api.trongrid.io"
+                start_block = 0
+                "#,
+                legacy
+            ))
+            .unwrap_err();
+            assert!(error.to_string().contains("[[chains]]"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn rejects_non_positive_tron_request_rates() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(tron_options(Some(rate)).validate("TRON").is_err());
+        }
+        assert!(tron_options(Some(0.5)).validate("TRON").is_ok());
+        assert!(tron_options(None).validate("TRON").is_ok());
     }
 }
@@ -1,9 +1,11 @@
 use config::ConfigError;
 use std::env::VarError;
 use std::num::ParseIntError;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum AppError {
     #[error("Configuration error: {0}")]
     ConfigError(#[from] ConfigError),
@@ -12,6 +14,7 @@ pub enum AppError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("Watcher error: {0}")]
+    #[allow(dead_code)]
     WatcherError(String),
     #[error("Database error: {0}")]
     DatabaseError(String),
@@ -27,6 +30,19 @@ pub enum AppError {
     ApiError(String),
     #[error("JSON parse error: {0}")]
     JsonParseError(String),
+    #[error("HTTP error: {0}, body: {1}")]
+    HttpError(u16, String),
+    #[error("Rate limited (retry after {0:?}), body: {1}")]
+    RateLimited(Option<Duration>, String),
+    #[error("API key rate limited for {0:?}: {1}")]
+    ApiKeyRateLimited(Duration, String),
+    #[error("API quota exceeded (resumes in {0:?}): {1}")]
+    QuotaExceeded(Duration, String),
+    #[error("Chain mismatch: {0}")]
+    ChainMismatch(String),
+    #[error("Contract call reverted: {0}")]
+    ContractReverted(String),
 }
 
+#[allow(dead_code)]
 pub type ScannerResult<T> = Result<T, AppError>;
@@ -1,4 +1,7 @@
+pub mod abi;
 pub mod config;
 pub mod error;
 pub mod log;
+#[cfg(test)]
+pub mod test_http;
 pub mod tron;
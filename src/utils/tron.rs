@@ -3,9 +3,13 @@ use sha2::{Digest, Sha256};
 
 use super::error::AppError;
 
-/// 将十六进制形式的 TRON 地址转换为 Base58 地址（以 'T' 开头）
+/// 将十六进制形式的 TRON 地址转换为 Base58 地址（以 'T' 开头），
+/// 接受带 41 前缀的 42 位或不带前缀的 40 位十六进制
 pub fn hex_to_tron_address(hex: &str) -> Result<String, AppError> {
-    let hex = hex.trim_start_matches("41");
+    let hex = match hex.len() {
+        42 => hex.strip_prefix("41").unwrap_or(hex),
+        _ => hex,
+    };
     if hex.len() != 40 {
         return Err(AppError::ParseError(
             "Invalid Tron address length".to_string(),
@@ -21,7 +25,7 @@ pub fn hex_to_tron_address(hex: &str) -> Result<String, AppError> {
     let hash1 = hasher.finalize();
 
     let mut hasher = Sha256::new();
-    hasher.update(&hash1);
+    hasher.update(hash1);
     let hash2 = hasher.finalize();
 
     // 取前4个字节作为校验和
@@ -37,6 +41,7 @@ pub fn hex_to_tron_address(hex: &str) -> Result<String, AppError> {
 }
 
 /// 将十六进制形式的 TRON 交易哈希转换为正确的格式
+#[allow(dead_code)]
 pub fn hex_to_tron_txhash(hex_hash: &str) -> Result<String, String> {
     // 移除可能的 "0x" 前缀
     let clean_hex = hex_hash.trim_start_matches("0x");
@@ -49,3 +54,70 @@ pub fn hex_to_tron_txhash(hex_hash: &str) -> Result<String, String> {
     // 直接返回清理后的十六进制字符串
     Ok(clean_hex.to_string())
 }
+
+/// 将 Base58 形式的 TRON 地址转换为十六进制地址（以 "41" 开头），并校验校验和
+pub fn tron_address_to_hex(address: &str) -> Result<String, AppError> {
+    let decoded = bs58::decode(address)
+        .into_vec()
+        .map_err(|e| AppError::ParseError(e.to_string()))?;
+
+    if decoded.len() != 25 || decoded[0] != 0x41 {
+        return Err(AppError::ParseError(
+            "Invalid Tron address length".to_string(),
+        ));
+    }
+
+    let (payload, checksum) = decoded.split_at(21);
+    let hash = Sha256::digest(Sha256::digest(payload));
+    if &hash[..4] != checksum {
+        return Err(AppError::ParseError(
+            "Invalid Tron address checksum".to_string(),
+        ));
+    }
+
+    Ok(hex::encode(payload))
+}
+
+/// 节点返回的地址转换为 Base58，无法转换时原样保留
+pub fn tron_address_or_raw(hex: &str) -> String {
+    hex_to_tron_address(hex).unwrap_or_else(|_| hex.to_string())
+}
+
+/// TRC-10 的 asset_name 在 visible=false 时是十六进制编码的 token id（例如 "1002000"）
+pub fn decode_asset_name(asset_name: &str) -> String {
+    hex::decode(asset_name)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| asset_name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// USDT 合约地址的两种形式
+    const USDT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+    const USDT_HEX: &str = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+
+    #[test]
+    fn converts_between_base58_and_hex() {
+        assert_eq!(tron_address_to_hex(USDT).unwrap(), USDT_HEX);
+        assert_eq!(hex_to_tron_address(USDT_HEX).unwrap(), USDT);
+        assert_eq!(hex_to_tron_address(&USDT_HEX[2..]).unwrap(), USDT);
+    }
+
+    #[test]
+    fn rejects_bad_checksums_and_lengths() {
+        let error = tron_address_to_hex("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u").unwrap_err();
+        assert!(error.to_string().contains("checksum"), "{}", error);
+        assert!(tron_address_to_hex("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj").is_err());
+        assert!(tron_address_to_hex("0OIl").is_err());
+        assert!(hex_to_tron_address("41a614f8").is_err());
+    }
+
+    #[test]
+    fn keeps_unconvertible_node_addresses() {
+        assert_eq!(tron_address_or_raw(USDT_HEX), USDT);
+        assert_eq!(tron_address_or_raw("not-an-address"), "not-an-address");
+    }
+}